@@ -0,0 +1,229 @@
+use sha1::{Digest, Sha1};
+use std::{
+    cmp::Ordering,
+    fs::File,
+    io::{BufRead, BufReader, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::mstd::error::{ErrorCode::UnableToReadFile, GetInputError};
+
+// Below this many bytes between the bounds, the lines are read one by one.
+const SCAN_SIZE: u64 = 4096;
+
+/// Uppercase hexadecimal SHA-1 of `password`, as written in the Pwned Passwords files.
+pub fn sha1_hex(password: &str) -> String {
+    Sha1::digest(password.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect()
+}
+
+/**
+
+## Breached Password Check
+
+### Examples
+```
+use mcore::audit::breach::pwned;
+
+let path = std::env::temp_dir().join("mokuba-pwned-example.txt");
+std::fs::write(
+    &path,
+    "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:9545824\nB1B3773A05C0ED0176787A4F1574FF0075F7521E:42\n",
+).unwrap();
+
+assert_eq!(pwned(&path, "password").unwrap(), Some(9545824));
+assert_eq!(pwned(&path, "not in the file").unwrap(), None);
+```
+
+Looks the SHA-1 of `password` up in a local Pwned Passwords file, ordered by hash, with one `<SHA-1>:<count>`
+line per password, and returns how many times it was seen in breaches. The file is binary searched, so only a
+few blocks of it are read, whatever its size. Nothing leaves the machine.
+
+This function will return an error if the file cannot be read or if one of the lines it reads is not a
+`<SHA-1>:<count>` pair.
+*/
+pub fn pwned(path: &Path, password: &str) -> Result<Option<u64>, GetInputError> {
+    pwned_hash(path, &sha1_hex(password))
+}
+
+/// Works like `pwned`, with the hexadecimal SHA-1 of the password instead of the password itself.
+pub fn pwned_hash(path: &Path, hash: &str) -> Result<Option<u64>, GetInputError> {
+    let hash = hash.to_ascii_uppercase();
+    let file = File::open(path).map_err(|e| read_error(path, e))?;
+    let size = file.metadata().map_err(|e| read_error(path, e))?.len();
+    let mut reader = BufReader::new(file);
+
+    // Lines that start before `low` sort before the hash, the ones from `low` on are read one by one.
+    let (mut low, mut high) = (0, size);
+    while high - low > SCAN_SIZE {
+        let middle = low + (high - low) / 2;
+        match line_at(&mut reader, middle, path)? {
+            Some(line) if entry(&line, path)?.0.cmp(&hash) == Ordering::Less => low = middle,
+            _ => high = middle,
+        }
+    }
+
+    seek_line(&mut reader, low, path)?;
+    for line in reader.lines() {
+        let line = line.map_err(|e| read_error(path, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (key, count) = entry(&line, path)?;
+        match key.cmp(&hash) {
+            Ordering::Less => continue,
+            Ordering::Equal => return Ok(Some(count)),
+            Ordering::Greater => return Ok(None),
+        }
+    }
+
+    Ok(None)
+}
+
+// Moves the reader to the start of the first line that starts at or after `offset`.
+fn seek_line(reader: &mut BufReader<File>, offset: u64, path: &Path) -> Result<(), GetInputError> {
+    if offset == 0 {
+        return reader
+            .seek(SeekFrom::Start(0))
+            .map(|_| ())
+            .map_err(|e| read_error(path, e));
+    }
+
+    // Starting one byte early keeps the line that starts exactly at `offset`.
+    reader
+        .seek(SeekFrom::Start(offset - 1))
+        .map_err(|e| read_error(path, e))?;
+    reader
+        .read_until(b'\n', &mut Vec::new())
+        .map(|_| ())
+        .map_err(|e| read_error(path, e))
+}
+
+fn line_at(
+    reader: &mut BufReader<File>,
+    offset: u64,
+    path: &Path,
+) -> Result<Option<String>, GetInputError> {
+    seek_line(reader, offset, path)?;
+
+    let mut line = String::new();
+    match reader
+        .read_line(&mut line)
+        .map_err(|e| read_error(path, e))?
+    {
+        0 => Ok(None),
+        _ => Ok(Some(line)),
+    }
+}
+
+// Splits a `<SHA-1>:<count>` line.
+fn entry(line: &str, path: &Path) -> Result<(String, u64), GetInputError> {
+    line.trim()
+        .split_once(':')
+        .and_then(|(key, count)| Some((key.to_ascii_uppercase(), count.parse().ok()?)))
+        .ok_or_else(|| {
+            GetInputError::new(
+                UnableToReadFile,
+                format!(
+                    "Unable to read {}, {:?} is not a <SHA-1>:<count> line",
+                    path.display(),
+                    line.trim()
+                ),
+            )
+        })
+}
+
+fn read_error(path: &Path, e: std::io::Error) -> GetInputError {
+    GetInputError::new(
+        UnableToReadFile,
+        format!("Unable to read {} with error: {}", path.display(), e),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+    use tempfile::NamedTempFile;
+
+    // Writes a sorted file of random hashes, with the given passwords among them.
+    fn dump(passwords: &[(&str, u64)]) -> NamedTempFile {
+        let mut rng = StdRng::seed_from_u64(13);
+        let mut lines = (0..20000)
+            .map(|i| {
+                let mut bytes = [0u8; 20];
+                rng.fill_bytes(&mut bytes);
+                let hash = bytes
+                    .iter()
+                    .map(|b| format!("{:02X}", b))
+                    .collect::<String>();
+                format!("{}:{}", hash, i + 1)
+            })
+            .chain(
+                passwords
+                    .iter()
+                    .map(|(password, count)| format!("{}:{}", sha1_hex(password), count)),
+            )
+            .collect::<Vec<String>>();
+        lines.sort();
+
+        let file = NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), lines.join("\r\n")).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_sha1_hex() {
+        assert_eq!(
+            sha1_hex("password"),
+            "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"
+        );
+    }
+
+    #[test]
+    fn test_pwned_finds_every_password_of_the_file() {
+        let passwords = [("password", 9545824), ("123456", 37359195), ("hunter2", 30)];
+        let file = dump(&passwords);
+        let path = file.path();
+
+        for (password, count) in passwords {
+            assert_eq!(pwned(path, password).unwrap(), Some(count));
+        }
+        assert_eq!(pwned(path, "k$;)Bcbc9xE3e*rM").unwrap(), None);
+    }
+
+    #[test]
+    fn test_pwned_hash_finds_the_first_and_last_lines() {
+        let file = dump(&[]);
+        let path = file.path();
+        let content = std::fs::read_to_string(path).unwrap();
+        let lines = content.lines().collect::<Vec<&str>>();
+
+        for line in [lines[0], lines[lines.len() - 1]] {
+            let (hash, count) = line.split_once(':').unwrap();
+            assert_eq!(
+                pwned_hash(path, &hash.to_lowercase()).unwrap(),
+                Some(count.parse().unwrap())
+            );
+        }
+        assert_eq!(pwned_hash(path, &"0".repeat(40)).unwrap(), None);
+        assert_eq!(pwned_hash(path, &"F".repeat(40)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_pwned_is_err_when_the_file_is_missing_or_malformed() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.txt");
+        assert_eq!(
+            pwned(&missing, "password").unwrap_err().code,
+            UnableToReadFile
+        );
+
+        let path = dir.path().join("malformed.txt");
+        std::fs::write(&path, "not a hash\n").unwrap();
+        assert_eq!(pwned(&path, "password").unwrap_err().code, UnableToReadFile);
+    }
+}
@@ -0,0 +1,766 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::generator::passphrase::wordlist;
+
+// Most common passwords of public leaks, ranked from the most to the least common one, one per line.
+static COMMON_PASSWORDS: &str = include_str!("../../assets/common_passwords.txt");
+
+static PASSWORDS: OnceLock<HashMap<String, usize>> = OnceLock::new();
+static ENGLISH: OnceLock<HashMap<String, usize>> = OnceLock::new();
+
+// Longest token looked up in the dictionaries, no word of them is longer.
+const MAX_WORD_LENGTH: usize = 32;
+
+// Years outside of this range are not taken for dates.
+const MIN_YEAR: u32 = 1000;
+const MAX_YEAR: u32 = 2050;
+
+// Letters that each l33t character may stand for.
+static L33T: &[(char, &[char])] = &[
+    ('4', &['a']),
+    ('@', &['a']),
+    ('8', &['b']),
+    ('(', &['c']),
+    ('{', &['c']),
+    ('[', &['c']),
+    ('<', &['c']),
+    ('3', &['e']),
+    ('6', &['g']),
+    ('9', &['g']),
+    ('1', &['i', 'l']),
+    ('!', &['i']),
+    ('|', &['i', 'l']),
+    ('7', &['l', 't']),
+    ('0', &['o']),
+    ('$', &['s']),
+    ('5', &['s']),
+    ('+', &['t']),
+    ('%', &['x']),
+    ('2', &['z']),
+];
+
+/// Rows of a keyboard, each with its unshifted keys, its shifted keys and its horizontal offset in keys. A
+/// space is a gap. Keys of a slanted keyboard touch the two keys of the next row they overlap, the keys of a
+/// grid touch their eight neighbours.
+pub struct Keyboard {
+    pub name: &'static str,
+    rows: &'static [(&'static str, &'static str, f32)],
+    slanted: bool,
+}
+
+pub static KEYBOARDS: &[Keyboard] = &[
+    Keyboard {
+        name: "qwerty",
+        rows: &[
+            ("`1234567890-=", "~!@#$%^&*()_+", 0.0),
+            ("qwertyuiop[]\\", "QWERTYUIOP{}|", 1.5),
+            ("asdfghjkl;'", "ASDFGHJKL:\"", 1.75),
+            ("zxcvbnm,./", "ZXCVBNM<>?", 2.25),
+        ],
+        slanted: true,
+    },
+    Keyboard {
+        name: "keypad",
+        rows: &[
+            (" /*-", " /*-", 0.0),
+            ("789+", "789+", 0.0),
+            ("456", "456", 0.0),
+            ("123", "123", 0.0),
+            (" 0.", " 0.", 0.0),
+        ],
+        slanted: false,
+    },
+];
+
+impl Keyboard {
+    // Column, row and whether the shift key is needed to type `c`.
+    fn position(&self, c: char) -> Option<(f32, i32, bool)> {
+        self.rows
+            .iter()
+            .enumerate()
+            .find_map(|(y, (unshifted, shifted, offset))| {
+                let column = |keys: &str| keys.chars().position(|key| key == c && key != ' ');
+                match (column(unshifted), column(shifted)) {
+                    (Some(x), _) => Some((offset + x as f32, y as i32, false)),
+                    (None, Some(x)) => Some((offset + x as f32, y as i32, true)),
+                    (None, None) => None,
+                }
+            })
+    }
+
+    // Direction, as a row and a column step, from the key of `a` to the key of `b` when they touch.
+    fn direction(&self, a: char, b: char) -> Option<(i32, i32)> {
+        let ((xa, ya, _), (xb, yb, _)) = (self.position(a)?, self.position(b)?);
+        let (dx, dy) = (xb - xa, yb - ya);
+
+        let touch = match self.slanted {
+            true => (dy == 0 && dx.abs() == 1.0) || (dy.abs() == 1 && dx.abs() < 1.0),
+            false => dx.abs() <= 1.0 && dy.abs() <= 1 && (dx != 0.0 || dy != 0),
+        };
+
+        touch.then_some((dy, dx.signum() as i32 * (dx != 0.0) as i32))
+    }
+
+    fn is_shifted(&self, c: char) -> bool {
+        matches!(self.position(c), Some((_, _, true)))
+    }
+
+    /// Amount of keys and average amount of neighbours of a key.
+    pub fn stats(&self) -> (f64, f64) {
+        let keys = self
+            .rows
+            .iter()
+            .flat_map(|(unshifted, _, _)| unshifted.chars())
+            .filter(|c| *c != ' ')
+            .collect::<Vec<char>>();
+        let neighbours = keys
+            .iter()
+            .map(|a| {
+                keys.iter()
+                    .filter(|b| self.direction(*a, **b).is_some())
+                    .count()
+            })
+            .sum::<usize>();
+
+        (keys.len() as f64, neighbours as f64 / keys.len() as f64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dictionary {
+    Passwords,
+    English,
+    UserInputs,
+}
+
+/// What a part of a password was recognized as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pattern {
+    Dictionary {
+        dictionary: Dictionary,
+        word: String,
+        rank: usize,
+        reversed: bool,
+        l33t: Vec<(char, char)>,
+    },
+    Spatial {
+        keyboard: &'static str,
+        turns: usize,
+        shifted: usize,
+    },
+    Repeat {
+        base: String,
+        count: usize,
+    },
+    Sequence {
+        ascending: bool,
+    },
+    Date {
+        year: u32,
+        month: u32,
+        day: u32,
+        separator: Option<char>,
+    },
+    Year {
+        year: u32,
+    },
+    Bruteforce,
+}
+
+/// A pattern found between the characters `i` and `j` of a password, both included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub i: usize,
+    pub j: usize,
+    pub token: String,
+    pub pattern: Pattern,
+}
+
+impl Match {
+    fn new(chars: &[char], i: usize, j: usize, pattern: Pattern) -> Match {
+        Match {
+            i,
+            j,
+            token: chars[i..=j].iter().collect(),
+            pattern,
+        }
+    }
+}
+
+impl std::fmt::Display for Match {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.pattern {
+            Pattern::Dictionary {
+                dictionary,
+                word,
+                rank,
+                reversed,
+                l33t,
+            } => {
+                let name = match dictionary {
+                    Dictionary::Passwords => "common password",
+                    Dictionary::English => "english word",
+                    Dictionary::UserInputs => "user input",
+                };
+                write!(
+                    f,
+                    "dictionary {:?}, {} {:?} #{}",
+                    self.token, name, word, rank
+                )?;
+                if *reversed {
+                    write!(f, ", reversed")?;
+                }
+                if !l33t.is_empty() {
+                    write!(f, ", l33t")?;
+                }
+                Ok(())
+            }
+            Pattern::Spatial {
+                keyboard,
+                turns,
+                shifted,
+            } => write!(
+                f,
+                "spatial {:?}, {}, turns {}, shifted {}",
+                self.token, keyboard, turns, shifted
+            ),
+            Pattern::Repeat { base, count } => {
+                write!(f, "repeat {:?}, {:?} {} times", self.token, base, count)
+            }
+            Pattern::Sequence { ascending } => write!(
+                f,
+                "sequence {:?}, {}",
+                self.token,
+                if *ascending {
+                    "ascending"
+                } else {
+                    "descending"
+                }
+            ),
+            Pattern::Date {
+                year, month, day, ..
+            } => write!(f, "date {:?}, {}-{:02}-{:02}", self.token, year, month, day),
+            Pattern::Year { year } => write!(f, "year {:?}, {}", self.token, year),
+            Pattern::Bruteforce => write!(f, "bruteforce {:?}", self.token),
+        }
+    }
+}
+
+/// Every pattern found in `chars`, including the overlapping ones. `user_inputs` are ranked as words of their
+/// own dictionary, in the given order.
+pub fn omnimatch(chars: &[char], user_inputs: &[String]) -> Vec<Match> {
+    let user_inputs = ranked(user_inputs.iter().map(|s| s.as_str()));
+    let dictionaries = [
+        (Dictionary::Passwords, passwords()),
+        (Dictionary::English, english()),
+        (Dictionary::UserInputs, &user_inputs),
+    ];
+
+    let mut matches = Vec::new();
+    matches.extend(dictionary(chars, &dictionaries));
+    matches.extend(reversed(chars, &dictionaries));
+    matches.extend(l33t(chars, &dictionaries));
+    matches.extend(spatial(chars));
+    matches.extend(repeat(chars));
+    matches.extend(sequence(chars));
+    matches.extend(date(chars));
+    matches.extend(year(chars));
+
+    matches.sort_by_key(|m| (m.i, m.j));
+    matches
+}
+
+fn passwords() -> &'static HashMap<String, usize> {
+    PASSWORDS.get_or_init(|| ranked(COMMON_PASSWORDS.lines()))
+}
+
+// The EFF wordlist is not ranked, so every word counts as one of all of them.
+fn english() -> &'static HashMap<String, usize> {
+    ENGLISH.get_or_init(|| {
+        let words = wordlist();
+        words
+            .iter()
+            .map(|word| (word.to_string(), words.len()))
+            .collect()
+    })
+}
+
+fn ranked<'a>(words: impl Iterator<Item = &'a str>) -> HashMap<String, usize> {
+    let mut ranks = HashMap::new();
+    for (rank, word) in words.filter(|word| !word.is_empty()).enumerate() {
+        ranks.entry(word.to_lowercase()).or_insert(rank + 1);
+    }
+    ranks
+}
+
+// Lowercase every character on its own, so the indices of the password stay valid.
+fn lowercase(chars: &[char]) -> Vec<char> {
+    chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect()
+}
+
+fn dictionary(
+    chars: &[char],
+    dictionaries: &[(Dictionary, &HashMap<String, usize>)],
+) -> Vec<Match> {
+    let lower = lowercase(chars);
+    let mut matches = Vec::new();
+
+    for i in 0..chars.len() {
+        for j in i..chars.len().min(i + MAX_WORD_LENGTH) {
+            let word = lower[i..=j].iter().collect::<String>();
+            for (name, words) in dictionaries {
+                if let Some(rank) = words.get(&word) {
+                    let pattern = Pattern::Dictionary {
+                        dictionary: *name,
+                        word: word.clone(),
+                        rank: *rank,
+                        reversed: false,
+                        l33t: vec![],
+                    };
+                    matches.push(Match::new(chars, i, j, pattern));
+                }
+            }
+        }
+    }
+
+    matches
+}
+
+fn reversed(chars: &[char], dictionaries: &[(Dictionary, &HashMap<String, usize>)]) -> Vec<Match> {
+    let n = chars.len();
+    let backwards = chars.iter().rev().copied().collect::<Vec<char>>();
+
+    dictionary(&backwards, dictionaries)
+        .into_iter()
+        .filter(|m| m.token.chars().ne(m.token.chars().rev()))
+        .filter_map(|m| match m.pattern {
+            Pattern::Dictionary {
+                dictionary,
+                word,
+                rank,
+                ..
+            } => Some(Match::new(
+                chars,
+                n - 1 - m.j,
+                n - 1 - m.i,
+                Pattern::Dictionary {
+                    dictionary,
+                    word,
+                    rank,
+                    reversed: true,
+                    l33t: vec![],
+                },
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+fn l33t(chars: &[char], dictionaries: &[(Dictionary, &HashMap<String, usize>)]) -> Vec<Match> {
+    let lower = lowercase(chars);
+    let mut matches = Vec::new();
+
+    for i in 0..chars.len() {
+        for j in i + 1..chars.len().min(i + MAX_WORD_LENGTH) {
+            let token = &lower[i..=j];
+            let mut subs = L33T
+                .iter()
+                .filter(|(c, _)| token.contains(c))
+                .collect::<Vec<_>>();
+            if subs.is_empty() {
+                continue;
+            }
+            subs.sort_by_key(|(c, _)| *c);
+
+            // Every way to read the l33t characters of the token, one letter per l33t character.
+            let mut tables: Vec<Vec<(char, char)>> = vec![vec![]];
+            for (c, letters) in subs {
+                tables = tables
+                    .into_iter()
+                    .flat_map(|table| {
+                        letters.iter().map(move |letter| {
+                            let mut table = table.clone();
+                            table.push((*c, *letter));
+                            table
+                        })
+                    })
+                    .collect();
+            }
+
+            for table in tables {
+                let word = token
+                    .iter()
+                    .map(|c| table.iter().find(|(l33t, _)| l33t == c).map_or(*c, |s| s.1))
+                    .collect::<String>();
+                for (name, words) in dictionaries {
+                    if let Some(rank) = words.get(&word) {
+                        let pattern = Pattern::Dictionary {
+                            dictionary: *name,
+                            word: word.clone(),
+                            rank: *rank,
+                            reversed: false,
+                            l33t: table.clone(),
+                        };
+                        matches.push(Match::new(chars, i, j, pattern));
+                    }
+                }
+            }
+        }
+    }
+
+    matches
+}
+
+// Runs of at least 3 keys where every key touches the previous one, like `qwerty`, `zxcvfr` or `7896`.
+fn spatial(chars: &[char]) -> Vec<Match> {
+    let mut matches = Vec::new();
+
+    for keyboard in KEYBOARDS {
+        let mut i = 0;
+        while i + 2 < chars.len() {
+            let mut j = i + 1;
+            let mut last = None;
+            let mut turns = 0;
+            let mut shifted = keyboard.is_shifted(chars[i]) as usize;
+
+            while let Some(direction) = chars
+                .get(j)
+                .and_then(|c| keyboard.direction(chars[j - 1], *c))
+            {
+                if last != Some(direction) {
+                    turns += 1;
+                    last = Some(direction);
+                }
+                shifted += keyboard.is_shifted(chars[j]) as usize;
+                j += 1;
+            }
+
+            if j - i > 2 {
+                let pattern = Pattern::Spatial {
+                    keyboard: keyboard.name,
+                    turns,
+                    shifted,
+                };
+                matches.push(Match::new(chars, i, j - 1, pattern));
+            }
+            i = j;
+        }
+    }
+
+    matches
+}
+
+// The longest run of a repeated base from every position, like `aaa` or `abcabc`, with the shortest base.
+fn repeat(chars: &[char]) -> Vec<Match> {
+    let mut matches = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let mut best: Option<(usize, usize)> = None;
+        for size in 1..=(chars.len() - i) / 2 {
+            let base = &chars[i..i + size];
+            let count = chars[i..]
+                .chunks_exact(size)
+                .take_while(|chunk| chunk == &base)
+                .count();
+            if count > 1 && best.is_none_or(|(s, c)| size * count > s * c) {
+                best = Some((size, count));
+            }
+        }
+
+        match best {
+            Some((size, count)) => {
+                let pattern = Pattern::Repeat {
+                    base: chars[i..i + size].iter().collect(),
+                    count,
+                };
+                matches.push(Match::new(chars, i, i + size * count - 1, pattern));
+                i += size * count;
+            }
+            None => i += 1,
+        }
+    }
+
+    matches
+}
+
+// Runs of at least 3 characters of the same class with the same small step, like `abc`, `6543` or `1357`.
+fn sequence(chars: &[char]) -> Vec<Match> {
+    let class = |c: char| match c {
+        'a'..='z' => Some(0),
+        'A'..='Z' => Some(1),
+        '0'..='9' => Some(2),
+        _ => None,
+    };
+    let step = |i: usize| chars[i + 1] as i64 - chars[i] as i64;
+
+    let mut matches = Vec::new();
+    let mut i = 0;
+
+    while i + 1 < chars.len() {
+        let delta = step(i);
+        let mut j = i + 1;
+        while j + 1 < chars.len() && step(j) == delta {
+            j += 1;
+        }
+
+        let same_class =
+            class(chars[i]).is_some() && chars[i..=j].iter().all(|c| class(*c) == class(chars[i]));
+        if j - i >= 2 && delta != 0 && delta.abs() <= 5 && same_class {
+            let pattern = Pattern::Sequence {
+                ascending: delta > 0,
+            };
+            matches.push(Match::new(chars, i, j, pattern));
+        }
+        i = j;
+    }
+
+    matches
+}
+
+// Dates of 4 to 8 digits, like `13021990` or `0213`, or with separators, like `13/2/90`. Dates that are part
+// of longer ones are dropped.
+fn date(chars: &[char]) -> Vec<Match> {
+    let mut matches = Vec::new();
+
+    for i in 0..chars.len() {
+        for j in i + 3..chars.len().min(i + 12) {
+            let token = &chars[i..=j];
+            let found = match token.iter().all(|c| c.is_ascii_digit()) {
+                true => digits_date(token).map(|(year, month, day)| (year, month, day, None)),
+                false => separated_date(token),
+            };
+
+            if let Some((year, month, day, separator)) = found {
+                let pattern = Pattern::Date {
+                    year,
+                    month,
+                    day,
+                    separator,
+                };
+                matches.push(Match::new(chars, i, j, pattern));
+            }
+        }
+    }
+
+    let inner = |m: &Match| {
+        matches
+            .iter()
+            .any(|other| other != m && other.i <= m.i && other.j >= m.j)
+    };
+    matches.iter().filter(|m| !inner(m)).cloned().collect()
+}
+
+fn digits_date(token: &[char]) -> Option<(u32, u32, u32)> {
+    let splits: &[(usize, usize)] = match token.len() {
+        4 => &[(1, 2), (2, 3)],
+        5 => &[(1, 3), (2, 3)],
+        6 => &[(1, 2), (2, 4), (4, 5)],
+        7 => &[(1, 3), (2, 3), (4, 5), (4, 6)],
+        8 => &[(2, 4), (4, 6)],
+        _ => &[],
+    };
+    let number = |part: &[char]| part.iter().collect::<String>().parse::<u32>().ok();
+
+    // Of every way to read the digits, the date closest to this year is the likeliest.
+    splits
+        .iter()
+        .filter_map(|(k, l)| {
+            dmy([
+                number(&token[..*k])?,
+                number(&token[*k..*l])?,
+                number(&token[*l..])?,
+            ])
+        })
+        .min_by_key(|(year, _, _)| year.abs_diff(reference_year()))
+}
+
+fn separated_date(token: &[char]) -> Option<(u32, u32, u32, Option<char>)> {
+    let separator = *token.iter().find(|c| !c.is_ascii_digit())?;
+    if !" -/\\_.".contains(separator) {
+        return None;
+    }
+
+    let parts = token
+        .split(|c| *c == separator)
+        .map(|part| match part.iter().all(|c| c.is_ascii_digit()) {
+            true => part.iter().collect::<String>().parse::<u32>().ok(),
+            false => None,
+        })
+        .collect::<Option<Vec<u32>>>()?;
+    let lengths = token
+        .split(|c| *c == separator)
+        .map(|part| part.len())
+        .collect::<Vec<usize>>();
+
+    match (parts.as_slice(), lengths.as_slice()) {
+        ([a, b, c], [la, lb, lc]) if *la <= 4 && *lb <= 2 && *lc <= 4 => {
+            dmy([*a, *b, *c]).map(|(year, month, day)| (year, month, day, Some(separator)))
+        }
+        _ => None,
+    }
+}
+
+// Reads three numbers as a day, a month and a year, with the year either first or last.
+fn dmy(numbers: [u32; 3]) -> Option<(u32, u32, u32)> {
+    if numbers[1] == 0 || numbers[1] > 31 {
+        return None;
+    }
+    let over_31 = numbers.iter().filter(|n| **n > 31).count();
+    let over_12 = numbers.iter().filter(|n| **n > 12).count();
+    let zeros = numbers.iter().filter(|n| **n == 0).count();
+    if over_31 > 1 || over_12 == 3 || zeros > 1 {
+        return None;
+    }
+
+    let candidates = [
+        (numbers[2], [numbers[0], numbers[1]]),
+        (numbers[0], [numbers[2], numbers[1]]),
+    ];
+
+    for (year, rest) in candidates {
+        if (MIN_YEAR..=MAX_YEAR).contains(&year) {
+            return dm(rest).map(|(day, month)| (year, month, day));
+        }
+    }
+
+    candidates
+        .iter()
+        .filter(|(year, _)| *year <= 99)
+        .find_map(|(year, rest)| {
+            let year = if *year > 50 { 1900 + year } else { 2000 + year };
+            dm(*rest).map(|(day, month)| (year, month, day))
+        })
+}
+
+fn dm(numbers: [u32; 2]) -> Option<(u32, u32)> {
+    [(numbers[0], numbers[1]), (numbers[1], numbers[0])]
+        .into_iter()
+        .find(|(day, month)| (1..=31).contains(day) && (1..=12).contains(month))
+}
+
+// Recent years on their own, like `1987` or `2019`.
+fn year(chars: &[char]) -> Vec<Match> {
+    (0..chars.len().saturating_sub(3))
+        .filter_map(|i| {
+            let token = &chars[i..i + 4];
+            if !token.iter().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let year = token.iter().collect::<String>().parse::<u32>().ok()?;
+            (1900..=MAX_YEAR)
+                .contains(&year)
+                .then(|| Match::new(chars, i, i + 3, Pattern::Year { year }))
+        })
+        .collect()
+}
+
+/// Current year, which dates and years are compared against.
+pub fn reference_year() -> u32 {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    1970 + (seconds / 31_556_952) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(password: &str) -> Vec<Match> {
+        omnimatch(&password.chars().collect::<Vec<char>>(), &[])
+    }
+
+    #[test]
+    fn test_dictionary_and_l33t_matches() {
+        let matches = find("P@ssw0rd");
+
+        assert!(
+            matches.iter().any(|m| m.token == "P@ssw0rd"
+                && matches!(&m.pattern, Pattern::Dictionary { word, l33t, .. }
+                if word == "password" && l33t.contains(&('@', 'a')) && l33t.contains(&('0', 'o'))))
+        );
+    }
+
+    #[test]
+    fn test_reversed_and_user_input_matches() {
+        let chars = "drowssapmokuba".chars().collect::<Vec<char>>();
+        let matches = omnimatch(&chars, &["Mokuba".to_string()]);
+
+        assert!(matches.iter().any(|m| m.i == 0
+            && m.j == 7
+            && matches!(m.pattern, Pattern::Dictionary { reversed: true, .. })));
+        assert!(matches.iter().any(|m| m.token == "mokuba"
+            && matches!(
+                m.pattern,
+                Pattern::Dictionary {
+                    dictionary: Dictionary::UserInputs,
+                    rank: 1,
+                    ..
+                }
+            )));
+    }
+
+    #[test]
+    fn test_spatial_matches() {
+        let matches = find("zxcvFR");
+
+        assert!(matches.iter().any(|m| m.token == "zxcvFR"
+            && matches!(
+                m.pattern,
+                Pattern::Spatial {
+                    keyboard: "qwerty",
+                    turns: 2,
+                    shifted: 2
+                }
+            )));
+        assert!(find("7896").iter().any(|m| m.token == "7896"
+            && matches!(
+                m.pattern,
+                Pattern::Spatial {
+                    keyboard: "keypad",
+                    ..
+                }
+            )));
+    }
+
+    #[test]
+    fn test_repeat_and_sequence_matches() {
+        let matches = find("abcabcabc1357zzz");
+
+        assert!(matches.iter().any(|m| m.token == "abcabcabc"
+            && matches!(&m.pattern, Pattern::Repeat { base, count: 3 } if base == "abc")));
+        assert!(matches.iter().any(
+            |m| m.token == "1357" && matches!(m.pattern, Pattern::Sequence { ascending: true })
+        ));
+        assert!(matches.iter().any(|m| m.token == "zzz"));
+    }
+
+    #[test]
+    fn test_date_matches() {
+        let matches = find("x13/02/1990y0704");
+
+        assert!(matches.iter().any(|m| m.token == "13/02/1990"
+            && m.pattern
+                == Pattern::Date {
+                    year: 1990,
+                    month: 2,
+                    day: 13,
+                    separator: Some('/')
+                }));
+        assert!(matches
+            .iter()
+            .any(|m| m.token == "1990" && m.pattern == Pattern::Year { year: 1990 }));
+        assert!(find("19840704").iter().any(|m| m.pattern
+            == Pattern::Date {
+                year: 1984,
+                month: 7,
+                day: 4,
+                separator: None
+            }));
+    }
+}
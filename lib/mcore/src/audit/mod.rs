@@ -0,0 +1,4 @@
+// Checks of existing passwords, as opposed to the generators that create new ones.
+pub mod breach;
+pub mod matching;
+pub mod strength;
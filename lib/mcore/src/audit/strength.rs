@@ -0,0 +1,508 @@
+use std::{collections::BTreeMap, sync::OnceLock};
+
+use super::matching::{omnimatch, reference_year, Dictionary, Match, Pattern, KEYBOARDS};
+
+// Guesses a bruteforce attack needs for every character that matches no pattern.
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+// Least guesses of a pattern that covers part of the password, so the rest is not ignored.
+const MIN_SUBMATCH_GUESSES_SINGLE_CHAR: f64 = 10.0;
+const MIN_SUBMATCH_GUESSES_MULTI_CHAR: f64 = 50.0;
+// Cost of every extra pattern, so a sequence of many short patterns is not cheaper than a longer one.
+const MIN_GUESSES_BEFORE_GROWING_SEQUENCE: f64 = 10000.0;
+// Least amount of years between a date and this year.
+const MIN_YEAR_SPACE: f64 = 20.0;
+
+/// Attacker models, as a name and the guesses per second they try.
+pub static ATTACKS: &[(&str, f64)] = &[
+    ("online, throttled at 100/hour", 100.0 / 3600.0),
+    ("online, unthrottled at 10/s", 10.0),
+    ("offline, slow hash at 10k/s", 1e4),
+    ("offline, fast hash at 10B/s", 1e10),
+];
+
+static KEYBOARD_STATS: OnceLock<Vec<(f64, f64)>> = OnceLock::new();
+
+/// Estimated strength of a password: the guesses an attacker that knows common patterns needs, a score from 0
+/// (too guessable) to 4 (very unguessable), the patterns the estimate is made of and how to improve it.
+#[derive(Debug, Clone)]
+pub struct Strength {
+    pub guesses: f64,
+    pub score: u8,
+    pub sequence: Vec<Match>,
+    pub warning: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+impl Strength {
+    /// Seconds every attacker model of `ATTACKS` needs to find the password.
+    pub fn crack_times(&self) -> Vec<(&'static str, f64)> {
+        ATTACKS
+            .iter()
+            .map(|(name, rate)| (*name, self.guesses / rate))
+            .collect()
+    }
+}
+
+/**
+
+## Strength Estimator
+
+### Examples
+```
+use mcore::audit::strength::strength;
+
+let weak = strength("P@ssw0rd", &[]);
+assert_eq!(weak.score, 0);
+assert!(weak.warning.is_some());
+
+let strong = strength("correct-horse-battery-staple-4821", &[]);
+assert_eq!(strong.score, 4);
+```
+
+Estimates how many guesses an attacker needs to find `password`, in the style of zxcvbn: the password is
+split into the sequence of dictionary words, l33t and reversed words, keyboard walks, repeats, sequences,
+dates and bruteforced characters that is the easiest to guess. `user_inputs`, like the site or the username,
+are guessed before any other word.
+*/
+pub fn strength(password: &str, user_inputs: &[String]) -> Strength {
+    let chars = password.chars().collect::<Vec<char>>();
+    let (guesses, sequence) = most_guessable(&chars, user_inputs);
+    // Long random passwords overflow, but any amount of guesses this large is just as out of reach.
+    let guesses = guesses.min(f64::MAX);
+    let score = score(guesses);
+    let (warning, suggestions) = feedback(score, &sequence);
+
+    Strength {
+        guesses,
+        score,
+        sequence,
+        warning,
+        suggestions,
+    }
+}
+
+// A candidate for the easiest sequence of patterns that ends at a given character.
+#[derive(Clone)]
+struct Step {
+    m: Match,
+    pi: f64,
+    g: f64,
+}
+
+// Finds the sequence of non overlapping patterns that covers the password with the least guesses, with the
+// dynamic programming of zxcvbn: for every character and every amount of patterns, only the best sequence
+// ending there is kept.
+fn most_guessable(chars: &[char], user_inputs: &[String]) -> (f64, Vec<Match>) {
+    let n = chars.len();
+    if n == 0 {
+        return (1.0, vec![]);
+    }
+
+    let mut by_end = vec![Vec::new(); n];
+    for m in omnimatch(chars, user_inputs) {
+        by_end[m.j].push(m);
+    }
+
+    let mut optimal: Vec<BTreeMap<usize, Step>> = vec![BTreeMap::new(); n];
+    let update = |optimal: &mut Vec<BTreeMap<usize, Step>>, m: Match, l: usize| {
+        let mut pi = guesses(&m, n, user_inputs);
+        if l > 1 {
+            pi *= optimal[m.i - 1][&(l - 1)].pi;
+        }
+        let g = factorial(l) * pi + MIN_GUESSES_BEFORE_GROWING_SEQUENCE.powi(l as i32 - 1);
+
+        let beaten = optimal[m.j]
+            .iter()
+            .any(|(other, step)| *other <= l && step.g <= g);
+        if !beaten {
+            optimal[m.j].insert(l, Step { m, pi, g });
+        }
+    };
+
+    for (k, ends) in by_end.into_iter().enumerate() {
+        for m in ends {
+            match m.i {
+                0 => update(&mut optimal, m, 1),
+                i => {
+                    let lengths = optimal[i - 1].keys().copied().collect::<Vec<usize>>();
+                    for l in lengths {
+                        update(&mut optimal, m.clone(), l + 1);
+                    }
+                }
+            }
+        }
+
+        update(&mut optimal, bruteforce(chars, 0, k), 1);
+        for i in 1..=k {
+            let lengths = optimal[i - 1]
+                .iter()
+                .filter(|(_, step)| step.m.pattern != Pattern::Bruteforce)
+                .map(|(l, _)| *l)
+                .collect::<Vec<usize>>();
+            for l in lengths {
+                update(&mut optimal, bruteforce(chars, i, k), l + 1);
+            }
+        }
+    }
+
+    let (mut l, guesses) = optimal[n - 1]
+        .iter()
+        .map(|(l, step)| (*l, step.g))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((1, 1.0));
+
+    let mut sequence = Vec::new();
+    let mut k = n;
+    while k > 0 {
+        let step = &optimal[k - 1][&l];
+        sequence.push(step.m.clone());
+        k = step.m.i;
+        l -= 1;
+    }
+    sequence.reverse();
+
+    (guesses, sequence)
+}
+
+fn bruteforce(chars: &[char], i: usize, j: usize) -> Match {
+    Match {
+        i,
+        j,
+        token: chars[i..=j].iter().collect(),
+        pattern: Pattern::Bruteforce,
+    }
+}
+
+// Guesses needed to find the token of `m` knowing its pattern, in a password of `n` characters.
+fn guesses(m: &Match, n: usize, user_inputs: &[String]) -> f64 {
+    let length = m.token.chars().count();
+
+    let guesses = match &m.pattern {
+        Pattern::Bruteforce => {
+            let min = match length {
+                1 => MIN_SUBMATCH_GUESSES_SINGLE_CHAR,
+                _ => MIN_SUBMATCH_GUESSES_MULTI_CHAR,
+            };
+            BRUTEFORCE_CARDINALITY.powi(length as i32).max(min + 1.0)
+        }
+        Pattern::Dictionary {
+            rank,
+            reversed,
+            l33t,
+            ..
+        } => {
+            *rank as f64
+                * uppercase_variations(&m.token)
+                * l33t_variations(&m.token, l33t)
+                * if *reversed { 2.0 } else { 1.0 }
+        }
+        Pattern::Spatial {
+            keyboard,
+            turns,
+            shifted,
+        } => spatial_guesses(keyboard, length, *turns, *shifted),
+        Pattern::Repeat { base, count } => {
+            most_guessable(&base.chars().collect::<Vec<char>>(), user_inputs).0 * *count as f64
+        }
+        Pattern::Sequence { ascending } => {
+            let first = m.token.chars().next().unwrap_or_default();
+            let base = match first {
+                'a' | 'A' | 'z' | 'Z' | '0' | '1' | '9' => 4.0,
+                c if c.is_ascii_digit() => 10.0,
+                _ => 26.0,
+            };
+            base * if *ascending { 1.0 } else { 2.0 } * length as f64
+        }
+        Pattern::Date {
+            year, separator, ..
+        } => year_space(*year) * 365.0 * if separator.is_some() { 4.0 } else { 1.0 },
+        Pattern::Year { year } => year_space(*year),
+    };
+
+    match length < n {
+        true if length == 1 => guesses.max(MIN_SUBMATCH_GUESSES_SINGLE_CHAR),
+        true => guesses.max(MIN_SUBMATCH_GUESSES_MULTI_CHAR),
+        false => guesses,
+    }
+}
+
+fn year_space(year: u32) -> f64 {
+    (year.abs_diff(reference_year()) as f64).max(MIN_YEAR_SPACE)
+}
+
+// Ways to capitalize a word: only the first or the last letter, or every letter, are the usual ones.
+fn uppercase_variations(token: &str) -> f64 {
+    let upper = token.chars().filter(|c| c.is_uppercase()).count();
+    let lower = token.chars().filter(|c| c.is_lowercase()).count();
+    let first = token.chars().next().is_some_and(|c| c.is_uppercase());
+    let last = token.chars().last().is_some_and(|c| c.is_uppercase());
+
+    match (upper, lower) {
+        (0, _) => 1.0,
+        (_, 0) => 2.0,
+        (1, _) if first || last => 2.0,
+        (upper, lower) => (1..=upper.min(lower))
+            .map(|i| choose(upper + lower, i))
+            .sum(),
+    }
+}
+
+// Ways to substitute some of the letters of a word with the l33t characters of `table`.
+fn l33t_variations(token: &str, table: &[(char, char)]) -> f64 {
+    let token = token.to_lowercase();
+
+    table
+        .iter()
+        .map(|(l33t, letter)| {
+            let subbed = token.chars().filter(|c| c == l33t).count();
+            let unsubbed = token.chars().filter(|c| c == letter).count();
+            match subbed == 0 || unsubbed == 0 {
+                true => 2.0,
+                false => (1..=subbed.min(unsubbed))
+                    .map(|i| choose(subbed + unsubbed, i))
+                    .sum(),
+            }
+        })
+        .product()
+}
+
+fn spatial_guesses(keyboard: &str, length: usize, turns: usize, shifted: usize) -> f64 {
+    let stats = KEYBOARD_STATS.get_or_init(|| KEYBOARDS.iter().map(|k| k.stats()).collect());
+    let (keys, degree) = KEYBOARDS
+        .iter()
+        .position(|k| k.name == keyboard)
+        .map_or((0.0, 0.0), |index| stats[index]);
+
+    let mut guesses = 0.0;
+    for i in 2..=length {
+        for j in 1..=turns.min(i - 1) {
+            guesses += choose(i - 1, j - 1) * keys * degree.powi(j as i32);
+        }
+    }
+
+    let unshifted = length - shifted;
+    match (shifted, unshifted) {
+        (0, _) => guesses,
+        (_, 0) => guesses * 2.0,
+        (shifted, unshifted) => {
+            guesses
+                * (1..=shifted.min(unshifted))
+                    .map(|i| choose(shifted + unshifted, i))
+                    .sum::<f64>()
+        }
+    }
+}
+
+fn choose(n: usize, k: usize) -> f64 {
+    (1..=k).fold(1.0, |acc, i| acc * (n + 1 - i) as f64 / i as f64)
+}
+
+fn factorial(n: usize) -> f64 {
+    (2..=n).map(|i| i as f64).product()
+}
+
+// Scores 0 to 4 start at 10^3, 10^6, 10^8 and 10^10 guesses, with a small margin.
+fn score(guesses: f64) -> u8 {
+    [1e3, 1e6, 1e8, 1e10]
+        .iter()
+        .take_while(|threshold| guesses >= **threshold + 5.0)
+        .count() as u8
+}
+
+fn feedback(score: u8, sequence: &[Match]) -> (Option<String>, Vec<String>) {
+    if score > 2 {
+        return (None, vec![]);
+    }
+
+    let mut suggestions = vec!["Add another word or two. Uncommon words are better.".to_string()];
+
+    let longest = match sequence
+        .iter()
+        .filter(|m| m.pattern != Pattern::Bruteforce)
+        .max_by_key(|m| m.token.chars().count())
+    {
+        Some(longest) => longest,
+        None => return (None, suggestions),
+    };
+    let sole = sequence.len() == 1;
+
+    let warning = match &longest.pattern {
+        Pattern::Dictionary {
+            dictionary,
+            rank,
+            reversed,
+            l33t,
+            ..
+        } => {
+            let token = &longest.token;
+            if token.chars().next().is_some_and(|c| c.is_uppercase()) {
+                suggestions.push("Capitalization doesn't help very much".to_string());
+            } else if token.chars().all(|c| !c.is_lowercase()) && token != &token.to_lowercase() {
+                suggestions
+                    .push("All-uppercase is almost as easy to guess as all-lowercase".to_string());
+            }
+            if *reversed && token.chars().count() >= 4 {
+                suggestions.push("Reversed words aren't much harder to guess".to_string());
+            }
+            if !l33t.is_empty() {
+                suggestions.push(
+                    "Predictable substitutions like '@' instead of 'a' don't help very much"
+                        .to_string(),
+                );
+            }
+
+            match dictionary {
+                Dictionary::Passwords if sole && *rank <= 10 && l33t.is_empty() && !reversed => {
+                    Some("This is a top-10 common password")
+                }
+                Dictionary::Passwords if sole && *rank <= 100 && l33t.is_empty() && !reversed => {
+                    Some("This is a top-100 common password")
+                }
+                Dictionary::Passwords => Some("This is similar to a commonly used password"),
+                Dictionary::English if sole => Some("A word by itself is easy to guess"),
+                Dictionary::English => None,
+                Dictionary::UserInputs => {
+                    Some("Words related to you or to the site are easy to guess")
+                }
+            }
+        }
+        Pattern::Spatial { turns, .. } => {
+            suggestions.push("Use a longer keyboard pattern with more turns".to_string());
+            match turns {
+                1 => Some("Straight rows of keys are easy to guess"),
+                _ => Some("Short keyboard patterns are easy to guess"),
+            }
+        }
+        Pattern::Repeat { base, .. } => {
+            suggestions.push("Avoid repeated words and characters".to_string());
+            match base.chars().count() {
+                1 => Some("Repeats like \"aaa\" are easy to guess"),
+                _ => Some(
+                    "Repeats like \"abcabcabc\" are only slightly harder to guess than \"abc\"",
+                ),
+            }
+        }
+        Pattern::Sequence { .. } => {
+            suggestions.push("Avoid sequences".to_string());
+            Some("Sequences like abc or 6543 are easy to guess")
+        }
+        Pattern::Date { .. } | Pattern::Year { .. } => {
+            suggestions.push("Avoid dates and years that are associated with you".to_string());
+            Some("Dates are often easy to guess")
+        }
+        Pattern::Bruteforce => None,
+    };
+
+    (warning.map(|w| w.to_string()), suggestions)
+}
+
+/// Describes `seconds` with its largest unit, like `3 hours` or `centuries`.
+pub fn display_time(seconds: f64) -> String {
+    const MINUTE: f64 = 60.0;
+    const HOUR: f64 = MINUTE * 60.0;
+    const DAY: f64 = HOUR * 24.0;
+    const MONTH: f64 = DAY * 31.0;
+    const YEAR: f64 = MONTH * 12.0;
+    const CENTURY: f64 = YEAR * 100.0;
+
+    let (amount, unit) = match seconds {
+        s if s < 1.0 => return "less than a second".to_string(),
+        s if s < MINUTE => (s, "second"),
+        s if s < HOUR => (s / MINUTE, "minute"),
+        s if s < DAY => (s / HOUR, "hour"),
+        s if s < MONTH => (s / DAY, "day"),
+        s if s < YEAR => (s / MONTH, "month"),
+        s if s < CENTURY => (s / YEAR, "year"),
+        _ => return "centuries".to_string(),
+    };
+
+    match amount.round() as u64 {
+        1 => format!("1 {}", unit),
+        amount => format!("{} {}s", amount, unit),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_common_passwords_are_weak() {
+        for password in ["password", "123456", "qwerty", "P@ssw0rd", "drowssap"] {
+            let estimate = strength(password, &[]);
+            assert_eq!(estimate.score, 0, "{}", password);
+            assert!(estimate.warning.is_some(), "{}", password);
+        }
+        assert_eq!(
+            strength("password", &[]).warning.unwrap(),
+            "This is a top-10 common password"
+        );
+    }
+
+    #[test]
+    fn test_patterns_are_found() {
+        let patterns = |password: &str| {
+            strength(password, &[])
+                .sequence
+                .into_iter()
+                .map(|m| m.pattern)
+                .collect::<Vec<Pattern>>()
+        };
+
+        assert!(matches!(
+            patterns("asdfghjkl;")[..],
+            [Pattern::Spatial { .. }]
+        ));
+        assert!(matches!(
+            patterns("abcdefgh")[..],
+            [Pattern::Sequence { .. }]
+        ));
+        assert!(matches!(
+            patterns("zzzzzzzzzz")[..],
+            [Pattern::Repeat { .. }]
+        ));
+        assert!(matches!(patterns("13/02/1990")[..], [Pattern::Date { .. }]));
+    }
+
+    #[test]
+    fn test_user_inputs_lower_the_estimate() {
+        let without = strength("mokuba", &[]);
+        let with = strength("mokuba", &["mokuba".to_string()]);
+
+        assert!(with.guesses < without.guesses);
+        assert_eq!(
+            with.warning.unwrap(),
+            "Words related to you or to the site are easy to guess"
+        );
+    }
+
+    #[test]
+    fn test_random_passwords_are_strong() {
+        let estimate = strength("k$;)Bcbc9xE3e*rM", &[]);
+
+        assert_eq!(estimate.score, 4);
+        assert!(estimate.warning.is_none());
+        assert!(estimate.suggestions.is_empty());
+    }
+
+    #[test]
+    fn test_sequence_covers_the_password() {
+        let estimate = strength("Tr0ub4dor&3horse1987", &[]);
+        let covered = estimate
+            .sequence
+            .iter()
+            .map(|m| m.token.as_str())
+            .collect::<String>();
+
+        assert_eq!(covered, "Tr0ub4dor&3horse1987");
+        assert!(estimate.guesses > 1e6);
+    }
+
+    #[test]
+    fn test_display_time() {
+        assert_eq!(display_time(0.5), "less than a second");
+        assert_eq!(display_time(1.0), "1 second");
+        assert_eq!(display_time(7200.0), "2 hours");
+        assert_eq!(display_time(1e12), "centuries");
+    }
+}
@@ -0,0 +1,86 @@
+use p12_keystore::{Certificate, KeyStore, KeyStoreEntry, PrivateKey, PrivateKeyChain};
+
+use super::authority::CertifiedKey;
+use crate::mstd::error::{ErrorCode::UnableToEncodeT, GenError};
+
+/// The certificate followed by the CA that signed it, as servers expect their chain.
+pub fn pem_chain(leaf: &CertifiedKey, ca: &CertifiedKey) -> String {
+    format!("{}{}", leaf.cert, ca.cert)
+}
+
+/**
+
+## PKCS#12 Bundle
+
+### Examples
+```
+use mcore::cert::{authority::{create_ca, issue}, bundle::pkcs12};
+use rand::rngs::OsRng;
+
+let ca = create_ca("dev", 30, &mut OsRng).unwrap();
+let leaf = issue(&ca, "app", &["localhost".to_string()], 30, &mut OsRng).unwrap();
+
+assert!(!pkcs12(&leaf, &ca, "correct horse battery staple").unwrap().is_empty());
+```
+
+Bundles the key and the certificate chain of `leaf` in a PKCS#12 file, encrypted with `passphrase` using
+PBES2 with AES-256 and HMAC-SHA256.
+
+This function will return an error if the key or one of the certificates cannot be decoded.
+*/
+pub fn pkcs12(
+    leaf: &CertifiedKey,
+    ca: &CertifiedKey,
+    passphrase: &str,
+) -> Result<Vec<u8>, GenError> {
+    let der = |text: &str| {
+        pem::parse(text)
+            .map(|pem| pem.into_contents())
+            .map_err(|e| error(e.to_string()))
+    };
+
+    let key = PrivateKey::from_der(&der(&leaf.key)?).map_err(|e| error(e.to_string()))?;
+    let certs = [&leaf.cert, &ca.cert]
+        .into_iter()
+        .map(|cert| Certificate::from_der(&der(cert)?).map_err(|e| error(e.to_string())))
+        .collect::<Result<Vec<Certificate>, GenError>>()?;
+
+    let mut store = KeyStore::new();
+    store.add_entry(
+        &leaf.name,
+        KeyStoreEntry::PrivateKeyChain(PrivateKeyChain::new(leaf.name.as_str(), key, certs)),
+    );
+
+    store
+        .writer(passphrase)
+        .write()
+        .map_err(|e| error(e.to_string()))
+}
+
+fn error(cause: String) -> GenError {
+    GenError::new(
+        UnableToEncodeT,
+        format!("Unable to create the PKCS#12 bundle: {}", cause),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::authority::{create_ca, issue};
+    use p12_keystore::Pkcs12ImportPolicy;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_pkcs12_opens_with_the_passphrase() {
+        let ca = create_ca("dev", 30, &mut OsRng).unwrap();
+        let leaf = issue(&ca, "app", &["localhost".to_string()], 30, &mut OsRng).unwrap();
+        let bundle = pkcs12(&leaf, &ca, "hunter2").unwrap();
+
+        let store = KeyStore::from_pkcs12(&bundle, "hunter2", Pkcs12ImportPolicy::Relaxed).unwrap();
+        let (alias, chain) = store.private_key_chain().unwrap();
+        assert_eq!(alias, "app");
+        assert_eq!(chain.certs().len(), 2);
+        assert!(KeyStore::from_pkcs12(&bundle, "wrong", Pkcs12ImportPolicy::Relaxed).is_err());
+    }
+}
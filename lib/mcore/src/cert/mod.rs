@@ -0,0 +1,3 @@
+// Local certificate authority and the certificates it issues, kept in the store with their keys in the vault.
+pub mod authority;
+pub mod bundle;
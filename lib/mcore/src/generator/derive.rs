@@ -0,0 +1,212 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+
+use crate::mstd::{
+    error::{
+        ErrorCode::{LengthMustBeGreaterThanZero, PolicyCannotBeSatisfied, UnableToDeriveKey},
+        GenError,
+    },
+    param::{DeriveParams, Kdf},
+};
+
+/**
+Stretches `secret` with Argon2id into `len` bytes.
+
+This function will return an error if the cost in `kdf` or the lengths are rejected by Argon2.
+*/
+pub fn stretch(secret: &[u8], salt: &[u8], kdf: &Kdf, len: usize) -> Result<Vec<u8>, GenError> {
+    let params = Params::new(kdf.memory, kdf.iterations, kdf.parallelism, Some(len))
+        .map_err(|e| GenError::new(UnableToDeriveKey, format!("Invalid KDF params: {}", e)))?;
+
+    let mut output = vec![0u8; len];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(secret, salt, &mut output)
+        .map_err(|e| GenError::new(UnableToDeriveKey, format!("Unable to derive key: {}", e)))?;
+
+    Ok(output)
+}
+
+/**
+
+## Stateless Password Derivation
+
+### Examples
+```
+use mcore::{generator::derive::derive, mstd::param::{DeriveParams, Kdf}};
+
+let params = DeriveParams {
+    master: "correct horse battery staple".to_string(),
+    site: "example.com".to_string(),
+    kdf: Kdf::new(64, 1, 1),
+    ..Default::default()
+};
+assert_eq!(derive(&params).unwrap(), derive(&params).unwrap());
+```
+
+Computes a site password from the master secret, the site, the login and the counter, so nothing has to be
+stored. The site is compared case-insensitively. The password honours `params.policy` like `gen` does.
+
+This function will return an error if the length is less than 1 or if the policy cannot be satisfied.
+*/
+pub fn derive(params: &DeriveParams) -> Result<String, GenError> {
+    let policy = &params.policy;
+    let required = policy.required();
+
+    if params.length == 0 {
+        return Err(GenError::new(
+            LengthMustBeGreaterThanZero,
+            "Please choose a length greater than 0".to_owned(),
+        ));
+    }
+
+    if params.length < required {
+        return Err(GenError::new(
+            PolicyCannotBeSatisfied,
+            format!(
+                "The policy requires at least {} characters but the length is {}",
+                required, params.length
+            ),
+        ));
+    }
+
+    let alphabet = policy.alphabet();
+    if alphabet.is_empty() {
+        return Err(GenError::new(
+            PolicyCannotBeSatisfied,
+            "The policy does not allow any character".to_owned(),
+        ));
+    }
+
+    // every draw consumes at most 16 bits, so 4 bytes per character leave plenty of headroom
+    let len = (params.length as usize * 4).max(64);
+    let mut entropy = Entropy(stretch(
+        params.master.as_bytes(),
+        &salt(params),
+        &params.kdf,
+        len,
+    )?);
+
+    let mut password = (0..params.length - required)
+        .map(|_| alphabet[entropy.draw(alphabet.len())])
+        .collect::<Vec<char>>();
+
+    for rule in policy.rules.iter().filter(|rule| rule.min > 0) {
+        let chars = rule.class.chars(&policy.excluded());
+        if chars.is_empty() {
+            return Err(GenError::new(
+                PolicyCannotBeSatisfied,
+                format!(
+                    "No characters left in class {:?} after exclusions",
+                    rule.class
+                ),
+            ));
+        }
+
+        for _ in 0..rule.min {
+            let c = chars[entropy.draw(chars.len())];
+            let position = entropy.draw(password.len() + 1);
+            password.insert(position, c);
+        }
+    }
+
+    Ok(password.into_iter().collect())
+}
+
+// Length-prefixed site, login and counter, so that no two different inputs share a salt.
+fn salt(params: &DeriveParams) -> Vec<u8> {
+    let site = params.site.trim().to_lowercase();
+    let counter = params.counter.to_be_bytes();
+    let fields: [&[u8]; 4] = [
+        b"mokuba",
+        site.as_bytes(),
+        params.login.as_bytes(),
+        &counter,
+    ];
+
+    fields
+        .iter()
+        .flat_map(|field| {
+            (field.len() as u32)
+                .to_be_bytes()
+                .into_iter()
+                .chain(field.iter().copied())
+        })
+        .collect()
+}
+
+// Big-endian integer that is consumed by repeated division, as LessPass does.
+struct Entropy(Vec<u8>);
+
+impl Entropy {
+    // Divides the integer by `n` in place and returns the remainder.
+    fn draw(&mut self, n: usize) -> usize {
+        let mut remainder = 0usize;
+        for byte in self.0.iter_mut() {
+            let acc = (remainder << 8) | *byte as usize;
+            *byte = (acc / n) as u8;
+            remainder = acc % n;
+        }
+        remainder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mstd::param::{CharClass, ClassRule, Policy};
+
+    fn params(site: &str, counter: u32) -> DeriveParams {
+        DeriveParams {
+            master: "master".to_string(),
+            site: site.to_string(),
+            login: "me@example.com".to_string(),
+            counter,
+            kdf: Kdf::new(64, 1, 1),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_derive_is_deterministic() {
+        let password = derive(&params("example.com", 1)).unwrap();
+
+        assert_eq!(password.len(), 16);
+        assert_eq!(password, derive(&params("EXAMPLE.com ", 1)).unwrap());
+        assert!(Policy::default().is_satisfied_by(&password));
+    }
+
+    #[test]
+    fn test_derive_depends_on_every_input() {
+        let password = derive(&params("example.com", 1)).unwrap();
+
+        assert_ne!(password, derive(&params("example.org", 1)).unwrap());
+        assert_ne!(password, derive(&params("example.com", 2)).unwrap());
+        assert_ne!(
+            password,
+            derive(&DeriveParams {
+                master: "other".to_string(),
+                ..params("example.com", 1)
+            })
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_derive_honours_the_policy() {
+        let password = derive(&DeriveParams {
+            length: 6,
+            policy: Policy::new(vec![ClassRule::new(CharClass::Digits, 6)], String::new()),
+            ..params("example.com", 1)
+        })
+        .unwrap();
+
+        assert!(password.chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn test_entropy_draw_is_a_long_division() {
+        let mut entropy = Entropy(vec![0x01, 0x00]);
+
+        assert_eq!(entropy.draw(10), 6);
+        assert_eq!(entropy.0, vec![0x00, 25]);
+    }
+}
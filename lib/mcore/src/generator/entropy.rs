@@ -0,0 +1,220 @@
+use crate::mstd::{
+    error::{ErrorCode::PolicyCannotBeSatisfied, GenError},
+    param::{GenerateParams, Mode, Passphrase, TokenFormat, DIGITS, SYMBOLS},
+};
+
+use super::{passphrase::wordlist, pattern, pin, pronounceable, recovery};
+
+/**
+Theoretical entropy in bits of the secrets produced by `gen` with the given params, based on the size of the
+alphabet and the length or, for passphrases, the size of the wordlist and the amount of words.
+
+# Examples
+
+```
+use mcore::{generator::entropy::entropy, mstd::param::{GenerateParams, Mode, Passphrase}};
+
+let params = GenerateParams {
+    mode: Mode::Passphrase(Passphrase { words: 6, ..Default::default() }),
+    ..Default::default()
+};
+assert_eq!(entropy(&params).round(), 78.0);
+```
+*/
+pub fn entropy(params: &GenerateParams) -> f64 {
+    match &params.mode {
+        Mode::Password | Mode::Pin => params.length as f64 * bits_per_symbol(params),
+        Mode::Passphrase(passphrase) => {
+            passphrase.words as f64 * bits_per_symbol(params) + extras(passphrase)
+        }
+        Mode::Pronounceable => pronounceable::bits(params),
+        Mode::Pattern(pattern) => pattern::bits(pattern, &params.policy.excluded()),
+        Mode::Token(TokenFormat::Uuid) => 122.0,
+        Mode::Token(TokenFormat::Ulid) => 80.0,
+        Mode::Token(_) => params.length as f64 * bits_per_symbol(params),
+        Mode::Recovery => recovery::bits(),
+    }
+}
+
+/**
+Derives the length, or the amount of words, from `params.min_entropy` when it is set, so the secret reaches
+at least that many bits. The params are returned untouched otherwise.
+
+This function will return an error if the alphabet is too small to reach any entropy, or if the params
+follow a pattern, a UUID or ULID format or recovery codes, whose length is fixed, that does not reach the
+target.
+*/
+pub fn fit(params: GenerateParams) -> Result<GenerateParams, GenError> {
+    let target = match params.min_entropy {
+        Some(target) => target as f64,
+        None => return Ok(params),
+    };
+
+    let fixed = match &params.mode {
+        Mode::Pattern(pattern) => Some(format!("The pattern {}", pattern)),
+        Mode::Token(TokenFormat::Uuid) => Some("A UUID".to_string()),
+        Mode::Token(TokenFormat::Ulid) => Some("A ULID".to_string()),
+        Mode::Recovery => Some("A recovery code".to_string()),
+        _ => None,
+    };
+    if let Some(name) = fixed {
+        let bits = entropy(&params);
+        return match bits >= target {
+            true => Ok(params),
+            false => Err(GenError::new(
+                PolicyCannotBeSatisfied,
+                format!(
+                    "{} only reaches {:.1} of {} bits of entropy",
+                    name, bits, target
+                ),
+            )),
+        };
+    }
+
+    let bits = bits_per_symbol(&params);
+    if bits <= 0.0 {
+        return Err(GenError::new(
+            PolicyCannotBeSatisfied,
+            format!(
+                "The alphabet is too small to reach {} bits of entropy",
+                target
+            ),
+        ));
+    }
+
+    let symbols = ((target / bits).ceil() as u32).max(1);
+
+    match params.mode {
+        Mode::Password | Mode::Pronounceable => Ok(GenerateParams {
+            length: symbols.max(params.policy.required()),
+            ..params
+        }),
+        Mode::Pin => Ok(GenerateParams {
+            length: symbols.max(pin::MIN_LENGTH),
+            ..params
+        }),
+        Mode::Token(_) => Ok(GenerateParams {
+            length: symbols,
+            ..params
+        }),
+        Mode::Recovery => Ok(params),
+        Mode::Passphrase(passphrase) => Ok(GenerateParams {
+            mode: Mode::Passphrase(Passphrase {
+                words: symbols,
+                ..passphrase
+            }),
+            ..params
+        }),
+        Mode::Pattern(_) => Ok(params),
+    }
+}
+
+// Bits contributed by every character of a password or every word of a passphrase.
+fn bits_per_symbol(params: &GenerateParams) -> f64 {
+    match &params.mode {
+        Mode::Password => (params.policy.symbols().len() as f64).log2(),
+        Mode::Passphrase(_) => (wordlist().len() as f64).log2(),
+        Mode::Pronounceable => pronounceable::bits_per_letter(params),
+        Mode::Pattern(_) => 0.0,
+        // Weak PINs are rejected, but they are too few to lower the entropy noticeably.
+        Mode::Pin => (DIGITS.len() as f64).log2(),
+        Mode::Token(_) => 8.0,
+        Mode::Recovery => (recovery::ALPHABET.len() as f64).log2(),
+    }
+}
+
+// Bits added by the digit and symbol appended to a random word of a passphrase.
+fn extras(passphrase: &Passphrase) -> f64 {
+    let position = (passphrase.words.max(1) as f64).log2();
+
+    [(passphrase.digit, DIGITS), (passphrase.symbol, SYMBOLS)]
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, set)| (set.chars().count() as f64).log2() + position)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mstd::param::{CharClass, ClassRule, Policy};
+
+    #[test]
+    fn test_entropy_of_a_password() {
+        let params = GenerateParams {
+            length: 10,
+            policy: Policy::new(vec![ClassRule::new(CharClass::Digits, 0)], String::new()),
+            ..Default::default()
+        };
+
+        assert!((entropy(&params) - 10.0 * 10f64.log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fit_derives_the_length_from_the_target() {
+        let params = GenerateParams {
+            length: 4,
+            min_entropy: Some(80),
+            ..Default::default()
+        };
+        let fitted = fit(params).unwrap();
+
+        assert_eq!(fitted.length, 13);
+        assert!(entropy(&fitted) >= 80.0);
+    }
+
+    #[test]
+    fn test_fit_derives_the_amount_of_words_from_the_target() {
+        let params = GenerateParams {
+            min_entropy: Some(100),
+            mode: Mode::Passphrase(Passphrase::default()),
+            ..Default::default()
+        };
+
+        match fit(params).unwrap().mode {
+            Mode::Passphrase(passphrase) => assert_eq!(passphrase.words, 8),
+            _ => panic!("expected a passphrase"),
+        }
+    }
+
+    #[test]
+    fn test_fit_fails_when_a_pattern_is_too_weak() {
+        let params = GenerateParams {
+            min_entropy: Some(40),
+            mode: Mode::Pattern("aaaa-9999".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(fit(params).unwrap_err().code, PolicyCannotBeSatisfied);
+    }
+
+    #[test]
+    fn test_fit_derives_the_amount_of_bytes_of_a_token() {
+        let token = |format: TokenFormat| GenerateParams {
+            min_entropy: Some(100),
+            mode: Mode::Token(format),
+            ..Default::default()
+        };
+
+        assert_eq!(fit(token(TokenFormat::Hex)).unwrap().length, 13);
+        assert!(fit(token(TokenFormat::Uuid)).is_ok());
+        assert_eq!(
+            fit(token(TokenFormat::Ulid)).unwrap_err().code,
+            PolicyCannotBeSatisfied
+        );
+    }
+
+    #[test]
+    fn test_fit_fails_with_a_single_character_alphabet() {
+        let params = GenerateParams {
+            min_entropy: Some(10),
+            policy: Policy::new(
+                vec![ClassRule::new(CharClass::Custom("a".to_string()), 0)],
+                String::new(),
+            ),
+            ..Default::default()
+        };
+
+        assert_eq!(fit(params).unwrap_err().code, PolicyCannotBeSatisfied);
+    }
+}
@@ -0,0 +1,13 @@
+// Generators for the secrets that are not plain random passwords.
+pub mod derive;
+pub mod entropy;
+pub mod keygen;
+pub mod otp;
+pub mod passphrase;
+pub mod pattern;
+pub mod pin;
+pub mod pronounceable;
+pub mod recovery;
+pub mod rng;
+pub mod token;
+pub mod username;
@@ -0,0 +1,178 @@
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+
+use crate::mstd::{
+    error::{ErrorCode::InvalidSecret, GenError},
+    otpauth::{Algorithm, OtpAuth, OtpKind},
+};
+
+/**
+
+## HMAC-Based One-Time Password
+
+### Examples
+```
+use mcore::{generator::otp::hotp, mstd::otpauth::Algorithm};
+
+assert_eq!(hotp(b"12345678901234567890", 0, Algorithm::Sha1, 6).unwrap(), "755224");
+```
+
+Computes the RFC 4226 code of `secret` for `counter`: the HMAC of the counter, dynamically truncated to 31 bits
+and reduced to `digits` decimal digits, keeping the leading zeros.
+
+This function will return an error if the secret is empty or if `digits` is not between 6 and 8.
+*/
+pub fn hotp(
+    secret: &[u8],
+    counter: u64,
+    algorithm: Algorithm,
+    digits: u32,
+) -> Result<String, GenError> {
+    if secret.is_empty() {
+        return Err(GenError::new(
+            InvalidSecret,
+            "Unable to compute a one-time password from an empty secret".to_owned(),
+        ));
+    }
+    if !(6..=8).contains(&digits) {
+        return Err(GenError::new(
+            InvalidSecret,
+            format!("Codes have 6 to 8 digits, not {}", digits),
+        ));
+    }
+
+    let message = counter.to_be_bytes();
+    let hash = match algorithm {
+        Algorithm::Sha1 => mac::<Hmac<Sha1>>(secret, &message),
+        Algorithm::Sha256 => mac::<Hmac<Sha256>>(secret, &message),
+        Algorithm::Sha512 => mac::<Hmac<Sha512>>(secret, &message),
+    };
+
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let truncated = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+
+    Ok(format!(
+        "{:0width$}",
+        truncated % 10u32.pow(digits),
+        width = digits as usize
+    ))
+}
+
+/**
+Computes the RFC 6238 code of `secret` at `time`, in seconds since the Unix epoch, with codes that change every
+`period` seconds.
+
+This function will return an error if the period is 0 or for the same reasons as `hotp`.
+*/
+pub fn totp(
+    secret: &[u8],
+    time: u64,
+    period: u64,
+    algorithm: Algorithm,
+    digits: u32,
+) -> Result<String, GenError> {
+    if period == 0 {
+        return Err(GenError::new(
+            InvalidSecret,
+            "The period must be greater than 0".to_owned(),
+        ));
+    }
+
+    hotp(secret, time / period, algorithm, digits)
+}
+
+/// Computes the current code of `auth`, at `time` for the time based ones.
+pub fn otp(auth: &OtpAuth, time: u64) -> Result<String, GenError> {
+    match auth.kind {
+        OtpKind::Totp { period } => totp(&auth.secret, time, period, auth.algorithm, auth.digits),
+        OtpKind::Hotp { counter } => hotp(&auth.secret, counter, auth.algorithm, auth.digits),
+    }
+}
+
+/// Seconds left at `time` before the code of a time based secret changes.
+pub fn remaining(time: u64, period: u64) -> u64 {
+    period - time % period.max(1)
+}
+
+fn mac<M: Mac + hmac::digest::KeyInit>(secret: &[u8], message: &[u8]) -> Vec<u8> {
+    // HMAC accepts keys of any length, so this cannot fail.
+    let mut mac = <M as Mac>::new_from_slice(secret).expect("HMAC takes keys of any size");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hotp_rfc_4226_vectors() {
+        let codes = [
+            "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583",
+            "399871", "520489",
+        ];
+
+        for (counter, code) in codes.iter().enumerate() {
+            assert_eq!(
+                hotp(b"12345678901234567890", counter as u64, Algorithm::Sha1, 6).unwrap(),
+                *code
+            );
+        }
+    }
+
+    #[test]
+    fn test_totp_rfc_6238_vectors() {
+        let seed = b"1234567890";
+        let seeds = [
+            (Algorithm::Sha1, seed.repeat(2)),
+            (Algorithm::Sha256, [seed.repeat(3), b"12".to_vec()].concat()),
+            (
+                Algorithm::Sha512,
+                [seed.repeat(6), b"1234".to_vec()].concat(),
+            ),
+        ];
+        let vectors = [
+            (59, ["94287082", "46119246", "90693936"]),
+            (1111111109, ["07081804", "68084774", "25091201"]),
+            (1111111111, ["14050471", "67062674", "99943326"]),
+            (1234567890, ["89005924", "91819424", "93441116"]),
+            (2000000000, ["69279037", "90698825", "38618901"]),
+            (20000000000, ["65353130", "77737706", "47863826"]),
+        ];
+
+        for (time, codes) in vectors {
+            for ((algorithm, secret), code) in seeds.iter().zip(codes) {
+                assert_eq!(totp(secret, time, 30, *algorithm, 8).unwrap(), code);
+            }
+        }
+    }
+
+    #[test]
+    fn test_otp_is_err_when_invalid() {
+        assert_eq!(
+            hotp(b"", 0, Algorithm::Sha1, 6).unwrap_err().code,
+            InvalidSecret
+        );
+        assert_eq!(
+            hotp(b"secret", 0, Algorithm::Sha1, 9).unwrap_err().code,
+            InvalidSecret
+        );
+        assert_eq!(
+            totp(b"secret", 0, 0, Algorithm::Sha1, 6).unwrap_err().code,
+            InvalidSecret
+        );
+    }
+
+    #[test]
+    fn test_remaining() {
+        assert_eq!(remaining(0, 30), 30);
+        assert_eq!(remaining(59, 30), 1);
+        assert_eq!(remaining(60, 60), 60);
+    }
+}
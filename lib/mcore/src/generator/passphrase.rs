@@ -0,0 +1,125 @@
+use rand::{seq::SliceRandom, CryptoRng, Rng, RngCore};
+use std::sync::OnceLock;
+
+use crate::mstd::{
+    error::{ErrorCode::LengthMustBeGreaterThanZero, GenError},
+    param::{Passphrase, DIGITS, SYMBOLS},
+};
+
+// EFF large wordlist (https://www.eff.org/dice), one `<dice rolls>\t<word>` pair per line.
+static EFF_LARGE_WORDLIST: &str = include_str!("../../assets/eff_large_wordlist.txt");
+
+static WORDLIST: OnceLock<Vec<&'static str>> = OnceLock::new();
+
+/// Returns the 7776 words of the bundled EFF large wordlist, parsed once.
+pub fn wordlist() -> &'static [&'static str] {
+    WORDLIST.get_or_init(|| {
+        EFF_LARGE_WORDLIST
+            .lines()
+            .filter_map(|line| line.split('\t').nth(1))
+            .collect()
+    })
+}
+
+/**
+
+## Passphrase Generator
+
+### Examples
+```
+use mcore::{generator::passphrase::passphrase, mstd::param::Passphrase};
+use rand::rngs::OsRng;
+
+let phrase = passphrase(&Passphrase { words: 6, ..Default::default() }, &mut OsRng).unwrap();
+assert_eq!(phrase.split('-').count(), 6);
+```
+
+When `digit` or `symbol` are set, the character is appended to one of the words picked at random.
+
+This function will return an error if the amount of words is less than 1.
+*/
+pub fn passphrase<R: RngCore + CryptoRng>(
+    params: &Passphrase,
+    rng: &mut R,
+) -> Result<String, GenError> {
+    if params.words == 0 {
+        return Err(GenError::new(
+            LengthMustBeGreaterThanZero,
+            "Please choose an amount of words greater than 0".to_owned(),
+        ));
+    }
+
+    let wordlist = wordlist();
+
+    let mut words = (0..params.words)
+        .filter_map(|_| wordlist.choose(rng))
+        .map(|word| match params.capitalize {
+            true => capitalize(word),
+            false => word.to_string(),
+        })
+        .collect::<Vec<String>>();
+
+    let extras = [(params.digit, DIGITS), (params.symbol, SYMBOLS)];
+    for (_, set) in extras.iter().filter(|(enabled, _)| *enabled) {
+        let chars = set.chars().collect::<Vec<char>>();
+        let index = rng.gen_range(0..words.len());
+        if let Some(c) = chars.choose(rng) {
+            words[index].push(*c);
+        }
+    }
+
+    Ok(words.join(&params.separator))
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_wordlist_has_every_dice_combination() {
+        let words = wordlist();
+        assert_eq!(words.len(), 7776);
+        assert_eq!(words[0], "abacus");
+        assert_eq!(words[7775], "zoom");
+    }
+
+    #[test]
+    fn test_passphrase_has_the_requested_words() {
+        let params = Passphrase::new(8, " ".to_string(), false, false, false);
+        let phrase = passphrase(&params, &mut OsRng).unwrap();
+        let wordlist = wordlist();
+
+        assert_eq!(phrase.split(' ').count(), 8);
+        assert!(phrase.split(' ').all(|word| wordlist.contains(&word)));
+    }
+
+    #[test]
+    fn test_passphrase_is_capitalized_and_has_extras() {
+        let params = Passphrase::new(4, " ".to_string(), true, true, true);
+        let phrase = passphrase(&params, &mut OsRng).unwrap();
+
+        assert!(phrase
+            .split(' ')
+            .all(|word| word.starts_with(|c: char| c.is_uppercase())));
+        assert!(phrase.chars().any(|c| c.is_ascii_digit()));
+        assert!(phrase.chars().any(|c| SYMBOLS.contains(c)));
+    }
+
+    #[test]
+    fn test_passphrase_fails_without_words() {
+        let params = Passphrase::new(0, "-".to_string(), false, false, false);
+        assert_eq!(
+            passphrase(&params, &mut OsRng).unwrap_err().code,
+            LengthMustBeGreaterThanZero
+        );
+    }
+}
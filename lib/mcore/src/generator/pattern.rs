@@ -0,0 +1,202 @@
+use rand::{seq::SliceRandom, CryptoRng, RngCore};
+
+use crate::mstd::{
+    error::{
+        ErrorCode::{InvalidPattern, LengthMustBeGreaterThanZero, PolicyCannotBeSatisfied},
+        GenError,
+    },
+    param::{CharClass, DIGITS, LOWER, SYMBOLS, UPPER},
+};
+
+/// A piece of a pattern, either a set of characters to pick from or a character copied as-is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Class(Vec<char>),
+    Literal(char),
+}
+
+/**
+Splits a pattern into tokens. Placeholders are `A` (upper), `a` (lower), `9` (digits), `!` (symbols) and
+`*` (any of them), as well as the hashcat masks `?u`, `?l`, `?d`, `?s`, `?a`, `?h` and `?H`. Any other
+character is a literal, and placeholders can be escaped with `\` (or `??` for a `?`). Characters in
+`exclude` are removed from every class.
+
+# Examples
+
+```
+use mcore::generator::pattern::{tokens, Token};
+
+let tokens = tokens("A-?d\\9", "").unwrap();
+assert_eq!(tokens.len(), 4);
+assert_eq!(tokens[1], Token::Literal('-'));
+assert_eq!(tokens[3], Token::Literal('9'));
+```
+*/
+pub fn tokens(pattern: &str, exclude: &str) -> Result<Vec<Token>, GenError> {
+    let class = |class: CharClass| Token::Class(class.chars(exclude));
+    let custom = |set: &str| class(CharClass::Custom(set.to_string()));
+    let any = || custom(&[UPPER, LOWER, DIGITS, SYMBOLS].concat());
+
+    let mut chars = pattern.chars();
+    let mut tokens = Vec::new();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            'A' => class(CharClass::Upper),
+            'a' => class(CharClass::Lower),
+            '9' => class(CharClass::Digits),
+            '!' => class(CharClass::Symbols),
+            '*' => any(),
+            '\\' => match chars.next() {
+                Some(escaped) => Token::Literal(escaped),
+                None => {
+                    return Err(GenError::new(
+                        InvalidPattern,
+                        format!("The pattern {} ends with an unfinished escape", pattern),
+                    ))
+                }
+            },
+            '?' => match chars.next() {
+                Some('u') => class(CharClass::Upper),
+                Some('l') => class(CharClass::Lower),
+                Some('d') => class(CharClass::Digits),
+                Some('s') => class(CharClass::Symbols),
+                Some('a') => any(),
+                Some('h') => custom("0123456789abcdef"),
+                Some('H') => custom("0123456789ABCDEF"),
+                Some('?') => Token::Literal('?'),
+                Some(other) => {
+                    return Err(GenError::new(
+                        InvalidPattern,
+                        format!("Unknown placeholder ?{} in the pattern {}", other, pattern),
+                    ))
+                }
+                None => {
+                    return Err(GenError::new(
+                        InvalidPattern,
+                        format!(
+                            "The pattern {} ends with an unfinished placeholder",
+                            pattern
+                        ),
+                    ))
+                }
+            },
+            literal => Token::Literal(literal),
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+/**
+
+## Pattern Generator
+
+### Examples
+```
+use mcore::generator::pattern::pattern;
+use rand::rngs::OsRng;
+
+let password = pattern("Aaaa-999999", "", &mut OsRng).unwrap();
+assert_eq!(password.len(), 11);
+assert_eq!(&password[4..5], "-");
+```
+
+This function will return an error if the pattern is empty, invalid or uses a class left empty by `exclude`.
+*/
+pub fn pattern<R: RngCore + CryptoRng>(
+    pattern: &str,
+    exclude: &str,
+    rng: &mut R,
+) -> Result<String, GenError> {
+    let tokens = tokens(pattern, exclude)?;
+    if tokens.is_empty() {
+        return Err(GenError::new(
+            LengthMustBeGreaterThanZero,
+            "Please choose a non empty pattern".to_owned(),
+        ));
+    }
+
+    tokens
+        .iter()
+        .map(|token| match token {
+            Token::Literal(c) => Ok(*c),
+            Token::Class(chars) => chars.choose(rng).copied().ok_or_else(|| {
+                GenError::new(
+                    PolicyCannotBeSatisfied,
+                    format!("A placeholder of {} has no characters left", pattern),
+                )
+            }),
+        })
+        .collect()
+}
+
+/// Theoretical entropy in bits of the passwords generated from `pattern`.
+pub fn bits(pattern: &str, exclude: &str) -> f64 {
+    tokens(pattern, exclude)
+        .unwrap_or_default()
+        .iter()
+        .map(|token| match token {
+            Token::Class(chars) if !chars.is_empty() => (chars.len() as f64).log2(),
+            _ => 0.0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_pattern_follows_the_mask() {
+        let password = pattern("Aaaa-9999-!!", "", &mut OsRng).unwrap();
+        let chars = password.chars().collect::<Vec<char>>();
+
+        assert_eq!(chars.len(), 12);
+        assert!(chars[0].is_ascii_uppercase());
+        assert!(chars[1..4].iter().all(|c| c.is_ascii_lowercase()));
+        assert_eq!(chars[4], '-');
+        assert!(chars[5..9].iter().all(|c| c.is_ascii_digit()));
+        assert_eq!(chars[9], '-');
+        assert!(chars[10..].iter().all(|c| SYMBOLS.contains(*c)));
+    }
+
+    #[test]
+    fn test_pattern_supports_hashcat_masks_and_escapes() {
+        let password = pattern("ID\\A?d?d?H??", "", &mut OsRng).unwrap();
+
+        assert!(password.starts_with("IDA"));
+        assert!(password[3..5].chars().all(|c| c.is_ascii_digit()));
+        assert!("0123456789ABCDEF".contains(&password[5..6]));
+        assert!(password.ends_with('?'));
+    }
+
+    #[test]
+    fn test_pattern_respects_exclusions() {
+        let password = pattern("99999999", "012345678", &mut OsRng).unwrap();
+        assert_eq!(password, "99999999");
+    }
+
+    #[test]
+    fn test_pattern_fails_on_invalid_placeholders() {
+        assert_eq!(
+            pattern("?x", "", &mut OsRng).unwrap_err().code,
+            InvalidPattern
+        );
+        assert_eq!(
+            pattern("aa\\", "", &mut OsRng).unwrap_err().code,
+            InvalidPattern
+        );
+        assert_eq!(
+            pattern("9", DIGITS, &mut OsRng).unwrap_err().code,
+            PolicyCannotBeSatisfied
+        );
+    }
+
+    #[test]
+    fn test_bits_of_a_pattern() {
+        assert!((bits("99-99", "") - 4.0 * 10f64.log2()).abs() < 1e-9);
+    }
+}
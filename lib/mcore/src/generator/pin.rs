@@ -0,0 +1,197 @@
+use rand::{CryptoRng, Rng, RngCore};
+
+use crate::mstd::error::{ErrorCode::PolicyCannotBeSatisfied, GenError};
+
+static COMMON_PINS: &str = include_str!("../../assets/common_pins.txt");
+
+/// Shortest PIN the generator accepts, shorter ones are almost all weak.
+pub const MIN_LENGTH: u32 = 4;
+
+// Weak PINs are drawn again, which keeps the draw uniform among the strong ones.
+const MAX_ATTEMPTS: u32 = 1000;
+
+// Day, month and year layouts of the dates people pick as PINs.
+static DATE_FORMATS: &[&str] = &[
+    "MMDD", "DDMM", "YYYY", "DDMMYY", "MMDDYY", "YYMMDD", "MMYYYY", "YYYYMM", "DDMMYYYY",
+    "MMDDYYYY", "YYYYMMDD",
+];
+
+/**
+
+## PIN Generator
+
+### Examples
+```
+use mcore::generator::pin::{is_weak, pin};
+use rand::rngs::OsRng;
+
+let pin = pin(6, &mut OsRng).unwrap();
+assert_eq!(pin.len(), 6);
+assert!(!is_weak(&pin));
+```
+
+Generates a numeric PIN of `length` digits that `is_weak` does not reject.
+
+This function will return an error if the length is shorter than `MIN_LENGTH`.
+*/
+pub fn pin<R: RngCore + CryptoRng>(length: u32, rng: &mut R) -> Result<String, GenError> {
+    if length < MIN_LENGTH {
+        return Err(GenError::new(
+            PolicyCannotBeSatisfied,
+            format!("Please choose a PIN of at least {} digits", MIN_LENGTH),
+        ));
+    }
+
+    for _ in 0..MAX_ATTEMPTS {
+        let pin = (0..length)
+            .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+            .collect::<String>();
+        if !is_weak(&pin) {
+            return Ok(pin);
+        }
+    }
+
+    Err(GenError::new(
+        PolicyCannotBeSatisfied,
+        format!(
+            "Unable to generate a PIN of {} digits that is not weak",
+            length
+        ),
+    ))
+}
+
+/**
+Checks whether `pin` is easy to guess: a run like `1234` or `7890`, a repeated digit or block like `1111` or
+`1212`, a palindrome, a calendar date or one of the most common PINs.
+
+# Examples
+
+```
+use mcore::generator::pin::is_weak;
+
+assert!(is_weak("4321"));
+assert!(is_weak("1225"));
+assert!(!is_weak("8403"));
+```
+*/
+pub fn is_weak(pin: &str) -> bool {
+    let digits = pin
+        .bytes()
+        .map(|b| b.wrapping_sub(b'0'))
+        .collect::<Vec<u8>>();
+
+    is_run(&digits)
+        || is_repeated(&digits)
+        || digits.iter().eq(digits.iter().rev())
+        || is_date(&digits)
+        || COMMON_PINS.lines().any(|common| common == pin)
+}
+
+// Every digit is one more, or one less, than the previous one, wrapping around from 9 to 0.
+fn is_run(digits: &[u8]) -> bool {
+    let steps = digits
+        .windows(2)
+        .map(|pair| (pair[1] as i16 - pair[0] as i16).rem_euclid(10))
+        .collect::<Vec<i16>>();
+
+    steps.iter().all(|step| *step == 1) || steps.iter().all(|step| *step == 9)
+}
+
+// The PIN is a shorter block repeated, like 1111, 1212 or 123123.
+fn is_repeated(digits: &[u8]) -> bool {
+    (1..digits.len())
+        .filter(|size| digits.len().is_multiple_of(*size))
+        .any(|size| digits.chunks(size).all(|chunk| chunk == &digits[..size]))
+}
+
+fn is_date(digits: &[u8]) -> bool {
+    DATE_FORMATS
+        .iter()
+        .filter(|format| format.len() == digits.len())
+        .any(|format| {
+            let field = |name: u8| {
+                format
+                    .bytes()
+                    .zip(digits)
+                    .filter(|(letter, _)| *letter == name)
+                    .fold(None, |acc: Option<u32>, (_, digit)| {
+                        Some(acc.unwrap_or(0) * 10 + *digit as u32)
+                    })
+            };
+
+            let month = field(b'M');
+            let valid_year = match (format.matches('Y').count(), field(b'Y')) {
+                (4, Some(year)) => (1900..=2099).contains(&year),
+                _ => true,
+            };
+            let valid_month = month.is_none_or(|month| (1..=12).contains(&month));
+            let valid_day =
+                field(b'D').is_none_or(|day| day >= 1 && day <= days_in(month.unwrap_or_default()));
+
+            valid_year && valid_month && valid_day
+        })
+}
+
+// February counts 29 days, since the year is rarely part of the PIN.
+fn days_in(month: u32) -> u32 {
+    match month {
+        2 => 29,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_pin_is_numeric_and_strong() {
+        for length in [4, 6, 8] {
+            for _ in 0..100 {
+                let pin = pin(length, &mut OsRng).unwrap();
+
+                assert_eq!(pin.len(), length as usize);
+                assert!(pin.chars().all(|c| c.is_ascii_digit()));
+                assert!(!is_weak(&pin));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pin_is_err_when_too_short() {
+        assert_eq!(
+            pin(3, &mut OsRng).unwrap_err().code,
+            PolicyCannotBeSatisfied
+        );
+    }
+
+    #[test]
+    fn test_is_weak_rejects_runs_and_repeats() {
+        for pin in [
+            "1234", "9876", "7890", "3210", "1111", "1212", "123123", "000000",
+        ] {
+            assert!(is_weak(pin), "{}", pin);
+        }
+    }
+
+    #[test]
+    fn test_is_weak_rejects_palindromes_and_dates() {
+        for pin in [
+            "1991", "385583", "0214", "3112", "1987", "250699", "19840704",
+        ] {
+            assert!(is_weak(pin), "{}", pin);
+        }
+        for pin in ["3241", "8403", "1399", "963074"] {
+            assert!(!is_weak(pin), "{}", pin);
+        }
+    }
+
+    #[test]
+    fn test_is_weak_rejects_common_pins() {
+        assert!(is_weak("2580"));
+        assert!(is_weak("147258"));
+        assert!(COMMON_PINS.lines().all(is_weak));
+    }
+}
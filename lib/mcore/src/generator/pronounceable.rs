@@ -0,0 +1,331 @@
+use rand::{seq::SliceRandom, CryptoRng, RngCore};
+
+use crate::mstd::{
+    error::{ErrorCode::PolicyCannotBeSatisfied, GenError},
+    param::{CharClass, GenerateParams, Policy},
+};
+
+static CONSONANTS: &[&str] = &[
+    "b", "c", "d", "f", "g", "h", "j", "k", "l", "m", "n", "p", "r", "s", "t", "v", "w", "z", "br",
+    "ch", "kr", "pr", "sh", "st", "th", "tr",
+];
+static VOWELS: &[&str] = &["a", "e", "i", "o", "u", "ai", "ea", "io", "ou"];
+
+// Amount of letters after which a `-` is placed, when the policy allows it.
+const WORD: usize = 6;
+
+/**
+
+## Pronounceable Password Generator
+
+### Examples
+```
+use mcore::{generator::pronounceable::pronounceable, mstd::param::GenerateParams};
+use rand::rngs::OsRng;
+
+let password = pronounceable(&GenerateParams::new(16, None), &mut OsRng).unwrap();
+assert_eq!(password.len(), 16);
+```
+
+Alternates consonant and vowel clusters, like `tavoru-kipesh`, made of the letters allowed by the classes of
+`params.policy`. Letters only allowed in uppercase are capitalized, the minimum of the upper class is met by
+capitalizing more letters, and the minimums of the digits, symbols and custom classes by appending their
+symbols at the end.
+
+This function will return an error if the policy allows no consonants or vowels, or if the length cannot hold
+the letters plus the required digits, symbols and custom symbols.
+*/
+pub fn pronounceable<R: RngCore + CryptoRng>(
+    params: &GenerateParams,
+    rng: &mut R,
+) -> Result<String, GenError> {
+    let policy = &params.policy;
+    let letters = letters(params)?;
+    let symbols = policy.symbols();
+    let allows = |c: char| symbols.contains(&c.to_string());
+
+    let consonants = clusters(CONSONANTS, &symbols);
+    let vowels = clusters(VOWELS, &symbols);
+    let separator = allows('-');
+
+    let mut body = Vec::<char>::new();
+    let mut word = 0;
+    let mut consonant = true;
+
+    while body.len() < letters {
+        if separator && word >= WORD && letters - body.len() > 1 {
+            body.push('-');
+            word = 0;
+            consonant = true;
+            continue;
+        }
+
+        let cluster = match consonant {
+            true => consonants.choose(rng),
+            false => vowels.choose(rng),
+        }
+        .ok_or_else(|| {
+            GenError::new(
+                PolicyCannotBeSatisfied,
+                "The classes of the policy leave no consonants or vowels".to_owned(),
+            )
+        })?;
+
+        body.extend(cluster.chars().take(letters - body.len()));
+        word += cluster.len();
+        consonant = !consonant;
+    }
+
+    for c in body
+        .iter_mut()
+        .filter(|c| c.is_alphabetic() && !allows(**c))
+    {
+        *c = c.to_ascii_uppercase();
+    }
+
+    let mut positions = (0..body.len())
+        .filter(|i| body[*i].is_ascii_lowercase() && allows(body[*i].to_ascii_uppercase()))
+        .collect::<Vec<usize>>();
+    positions.shuffle(rng);
+
+    let count = |body: &[char], f: fn(&char) -> bool| body.iter().filter(|c| f(c)).count();
+    let upper = (min_of(policy, CharClass::Upper) as usize)
+        .saturating_sub(count(&body, char::is_ascii_uppercase));
+    let spare = count(&body, char::is_ascii_lowercase)
+        .saturating_sub(min_of(policy, CharClass::Lower) as usize);
+    if upper > positions.len().min(spare) {
+        return Err(GenError::new(
+            PolicyCannotBeSatisfied,
+            format!("Unable to capitalize {} letters in {}", upper, letters),
+        ));
+    }
+    for i in positions.into_iter().take(upper) {
+        body[i] = body[i].to_ascii_uppercase();
+    }
+
+    let mut password = body.into_iter().collect::<String>();
+    for rule in policy
+        .rules
+        .iter()
+        .filter(|rule| !is_letter_case(&rule.class))
+    {
+        let symbols = policy.symbols_of(&rule.class);
+        for _ in 0..rule.min {
+            match symbols.choose(rng) {
+                Some(symbol) => password.push_str(symbol),
+                None => {
+                    return Err(GenError::new(
+                        PolicyCannotBeSatisfied,
+                        format!(
+                            "No characters left in class {:?} after exclusions",
+                            rule.class
+                        ),
+                    ))
+                }
+            }
+        }
+    }
+
+    Ok(password)
+}
+
+/// Theoretical entropy in bits of a pronounceable password generated with `params`.
+pub fn bits(params: &GenerateParams) -> f64 {
+    let policy = &params.policy;
+    let letters = letters(params).unwrap_or_default() as f64;
+    let log2 = |n: usize| (n as f64).log2();
+
+    let class_bits = policy
+        .rules
+        .iter()
+        .filter(|rule| !is_letter_case(&rule.class))
+        .map(|rule| rule.min as f64 * log2(policy.symbols_of(&rule.class).len()))
+        .sum::<f64>();
+
+    letters * bits_per_letter(params)
+        + class_bits
+        + min_of(policy, CharClass::Upper) as f64 * letters.max(1.0).log2()
+}
+
+/// Average bits of every letter, given by a consonant and a vowel cluster over their average length.
+pub fn bits_per_letter(params: &GenerateParams) -> f64 {
+    let symbols = params.policy.symbols();
+    let consonants = clusters(CONSONANTS, &symbols);
+    let vowels = clusters(VOWELS, &symbols);
+
+    if consonants.is_empty() || vowels.is_empty() {
+        return 0.0;
+    }
+
+    let average = |clusters: &[&str]| {
+        clusters.iter().map(|c| c.len()).sum::<usize>() as f64 / clusters.len() as f64
+    };
+
+    ((consonants.len() * vowels.len()) as f64).log2() / (average(&consonants) + average(&vowels))
+}
+
+// Amount of letters, including separators, left once the required digits, symbols and custom symbols are set
+// aside.
+fn letters(params: &GenerateParams) -> Result<usize, GenError> {
+    let policy = &params.policy;
+    let reserved = policy
+        .rules
+        .iter()
+        .filter(|rule| !is_letter_case(&rule.class))
+        .map(|rule| rule.min)
+        .sum::<u32>();
+
+    match params.length.checked_sub(reserved) {
+        Some(letters) if letters > 0 => Ok(letters as usize),
+        _ => Err(GenError::new(
+            PolicyCannotBeSatisfied,
+            format!(
+                "The policy requires {} digits, symbols and custom symbols but the length is {}",
+                reserved, params.length
+            ),
+        )),
+    }
+}
+
+fn min_of(policy: &Policy, class: CharClass) -> u32 {
+    policy
+        .rules
+        .iter()
+        .filter(|rule| rule.class == class)
+        .map(|rule| rule.min)
+        .sum()
+}
+
+// Whether the minimum of `class` is met by the case of the letters rather than by appended symbols.
+fn is_letter_case(class: &CharClass) -> bool {
+    matches!(class, CharClass::Upper | CharClass::Lower)
+}
+
+// Clusters of `set` whose every letter is among `symbols`, in lowercase or in uppercase.
+fn clusters(set: &[&'static str], symbols: &[String]) -> Vec<&'static str> {
+    let allows = |c: char| {
+        symbols.contains(&c.to_string()) || symbols.contains(&c.to_ascii_uppercase().to_string())
+    };
+
+    set.iter()
+        .filter(|cluster| cluster.chars().all(allows))
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mstd::param::ClassRule;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_pronounceable_satisfies_the_default_policy() {
+        for _ in 0..50 {
+            let params = GenerateParams::new(14, None);
+            let password = pronounceable(&params, &mut OsRng).unwrap();
+
+            assert_eq!(password.len(), 14);
+            assert!(params.policy.is_satisfied_by(&password));
+        }
+    }
+
+    #[test]
+    fn test_pronounceable_alternates_consonants_and_vowels() {
+        let params = GenerateParams {
+            length: 24,
+            policy: Policy::new(vec![ClassRule::new(CharClass::Lower, 1)], "-".to_string()),
+            ..Default::default()
+        };
+        let password = pronounceable(&params, &mut OsRng).unwrap();
+        let is_vowel = |c: char| "aeiou".contains(c);
+
+        assert_eq!(password.len(), 24);
+        assert!(password.chars().all(|c| c.is_ascii_lowercase()));
+        assert!(password
+            .chars()
+            .collect::<Vec<char>>()
+            .windows(3)
+            .all(|w| !w.iter().all(|c| !is_vowel(*c))));
+    }
+
+    #[test]
+    fn test_pronounceable_only_separates_words_when_symbols_are_allowed() {
+        let params = GenerateParams {
+            length: 24,
+            policy: Policy::new(vec![ClassRule::new(CharClass::Lower, 1)], String::new()),
+            ..Default::default()
+        };
+        let password = pronounceable(&params, &mut OsRng).unwrap();
+
+        assert!(params.policy.is_satisfied_by(&password), "{}", password);
+        assert!(!password.contains('-'));
+        assert!(pronounceable(&GenerateParams::new(24, None), &mut OsRng)
+            .unwrap()
+            .contains('-'));
+    }
+
+    #[test]
+    fn test_pronounceable_satisfies_the_classes_of_the_policy() {
+        let policies = [
+            Policy::new(vec![ClassRule::new(CharClass::Upper, 1)], String::new()),
+            Policy::new(
+                vec![
+                    ClassRule::new(CharClass::Upper, 3),
+                    ClassRule::new(CharClass::Lower, 2),
+                    ClassRule::new(CharClass::Digits, 2),
+                ],
+                "aeAE".to_string(),
+            ),
+            Policy::new(
+                vec![
+                    ClassRule::new(CharClass::Custom("bdkmaeiou".to_string()), 1),
+                    ClassRule::new(CharClass::Custom("!?".to_string()), 2),
+                ],
+                String::new(),
+            ),
+        ];
+
+        for policy in policies {
+            for _ in 0..50 {
+                let params = GenerateParams {
+                    length: 12,
+                    policy: policy.clone(),
+                    ..Default::default()
+                };
+                let password = pronounceable(&params, &mut OsRng).unwrap();
+
+                assert_eq!(password.chars().count(), 12);
+                assert!(policy.is_satisfied_by(&password), "{}", password);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pronounceable_fails_without_letters() {
+        let params = GenerateParams {
+            length: 12,
+            policy: Policy::new(
+                vec![
+                    ClassRule::new(CharClass::Digits, 1),
+                    ClassRule::new(CharClass::Symbols, 1),
+                ],
+                String::new(),
+            ),
+            ..Default::default()
+        };
+        assert_eq!(
+            pronounceable(&params, &mut OsRng).unwrap_err().code,
+            PolicyCannotBeSatisfied
+        );
+    }
+
+    #[test]
+    fn test_pronounceable_fails_when_the_length_is_too_short() {
+        let params = GenerateParams::new(2, None);
+        assert_eq!(
+            pronounceable(&params, &mut OsRng).unwrap_err().code,
+            PolicyCannotBeSatisfied
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use rand::{CryptoRng, Rng, RngCore};
+
+use crate::mstd::{
+    codec::Codec,
+    error::{
+        DecodeError,
+        ErrorCode::{InvalidChecksum, InvalidRecoveryCode},
+    },
+};
+
+/// Characters of the recovery codes: uppercase letters and digits, without 0, 1, I, L and O.
+pub static ALPHABET: &str = "ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+
+// Random characters of a code, followed by one check character, written in groups of 4.
+const RANDOM_LENGTH: usize = 11;
+const GROUP_LENGTH: usize = 4;
+
+/**
+
+## Recovery Code Generator
+
+### Examples
+```
+use mcore::generator::recovery::{is_valid, recovery_code};
+use rand::rngs::OsRng;
+
+let code = recovery_code(&mut OsRng);
+assert_eq!(code.len(), 14);
+assert!(is_valid(&code));
+assert!(is_valid(&code.to_lowercase().replace('-', " ")));
+```
+
+Generates a one-time recovery code like `ABCD-EFGH-JKLM`: 11 random characters of `ALPHABET` and a check
+character, a weighted sum modulo 31 that catches any mistyped character and any swap of two characters.
+*/
+pub fn recovery_code<R: RngCore + CryptoRng>(rng: &mut R) -> String {
+    let alphabet = ALPHABET.as_bytes();
+    let mut values = (0..RANDOM_LENGTH)
+        .map(|_| rng.gen_range(0..alphabet.len()))
+        .collect::<Vec<usize>>();
+    values.push(check(&values));
+
+    let code = values
+        .into_iter()
+        .map(|value| alphabet[value] as char)
+        .collect::<Vec<char>>();
+    code.chunks(GROUP_LENGTH)
+        .map(|group| group.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+/// Entropy in bits of a recovery code, the check character adds none.
+pub fn bits() -> f64 {
+    RANDOM_LENGTH as f64 * (ALPHABET.len() as f64).log2()
+}
+
+/// Whether `code` has a valid check character, ignoring case, dashes and spaces.
+pub fn is_valid(code: &str) -> bool {
+    normalize(code).is_ok()
+}
+
+/**
+Returns `code` as it is generated, in uppercase and grouped with dashes, so codes typed in any case or spacing
+can be compared.
+
+This function will return an error if the code has the wrong length, characters that are not in `ALPHABET` or
+a check character that does not match.
+*/
+pub fn normalize(code: &str) -> Result<String, DecodeError> {
+    let values = code
+        .chars()
+        .filter(|c| !matches!(c, '-' | ' '))
+        .map(|c| ALPHABET.find(c.to_ascii_uppercase()))
+        .collect::<Option<Vec<usize>>>()
+        .filter(|values| values.len() == RANDOM_LENGTH + 1)
+        .ok_or_else(|| {
+            DecodeError::new(
+                InvalidChecksum,
+                format!("{} is not a recovery code like ABCD-EFGH-JKLM", code),
+            )
+        })?;
+
+    let (random, sum) = values.split_at(RANDOM_LENGTH);
+    if check(random) != sum[0] {
+        return Err(DecodeError::new(
+            InvalidChecksum,
+            format!("The recovery code {} is mistyped", code),
+        ));
+    }
+
+    let code = values
+        .iter()
+        .map(|value| ALPHABET.as_bytes()[*value] as char)
+        .collect::<Vec<char>>();
+    Ok(code
+        .chunks(GROUP_LENGTH)
+        .map(|group| group.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join("-"))
+}
+
+// Sum of the values weighted by their position, modulo the size of the alphabet. 31 is prime and the weights
+// are distinct and below it, so a single substitution or a swap always changes the sum.
+fn check(values: &[usize]) -> usize {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| (i + 1) * value)
+        .sum::<usize>()
+        % ALPHABET.len()
+}
+
+/// A recovery code and whether it was already used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryCode {
+    pub code: String,
+    pub used: bool,
+}
+
+/// A batch of recovery codes as it is stored, one code per line, with `used` after the ones that were redeemed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoverySet {
+    pub codes: Vec<RecoveryCode>,
+}
+
+impl RecoverySet {
+    /**
+    Marks `code` as used and returns how many codes are left.
+
+    This function will return an error if the code is mistyped, is not part of the set or was already used.
+    */
+    pub fn redeem(&mut self, code: &str) -> Result<usize, DecodeError> {
+        let normalized = normalize(code)?;
+        let entry = self
+            .codes
+            .iter_mut()
+            .find(|entry| entry.code == normalized)
+            .ok_or_else(|| {
+                DecodeError::new(
+                    InvalidRecoveryCode,
+                    format!("{} is not one of the recovery codes", normalized),
+                )
+            })?;
+
+        if entry.used {
+            return Err(DecodeError::new(
+                InvalidRecoveryCode,
+                format!("The recovery code {} was already used", normalized),
+            ));
+        }
+        entry.used = true;
+
+        Ok(self.remaining())
+    }
+
+    /// Amount of codes that were not used yet.
+    pub fn remaining(&self) -> usize {
+        self.codes.iter().filter(|entry| !entry.used).count()
+    }
+}
+
+impl Codec<RecoverySet> for RecoverySet {
+    fn encode(&self) -> String {
+        self.codes
+            .iter()
+            .map(|entry| match entry.used {
+                true => format!("{}\tused\n", entry.code),
+                false => format!("{}\n", entry.code),
+            })
+            .collect()
+    }
+
+    /**
+    Decodes one code per line, ignoring empty lines. Only the first field of a line is the code, so the
+    output of `generate --recovery-codes` can be stored as-is, and a later `used` field marks it used.
+
+    # Examples
+
+    ```
+    use mcore::{generator::recovery::RecoverySet, mstd::codec::Codec};
+
+    let mut set = RecoverySet::decode(Some("AAAA-AAAA-AAAA\t54.5 bits\nBCDE-FGHJ-KMNM\tused\n")).unwrap();
+    assert_eq!(set.remaining(), 1);
+    assert_eq!(set.redeem("aaaa aaaa aaaa").unwrap(), 0);
+    assert!(set.redeem("AAAA-AAAA-AAAA").is_err());
+    ```
+    */
+    fn decode(s: Option<&str>) -> Result<RecoverySet, DecodeError> {
+        let codes = s
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut fields = line.split_whitespace();
+                Ok(RecoveryCode {
+                    code: normalize(fields.next().unwrap_or_default())?,
+                    used: fields.any(|field| field == "used"),
+                })
+            })
+            .collect::<Result<Vec<RecoveryCode>, DecodeError>>()?;
+
+        Ok(RecoverySet { codes })
+    }
+
+    fn is(s: String) -> bool {
+        RecoverySet::decode(Some(&s)).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_recovery_code_is_grouped_and_unambiguous() {
+        for _ in 0..100 {
+            let code = recovery_code(&mut OsRng);
+            let groups = code.split('-').collect::<Vec<&str>>();
+
+            assert_eq!(groups.len(), 3);
+            assert!(groups.iter().all(|group| group.len() == 4));
+            assert!(!code.contains(['0', '1', 'I', 'L', 'O']));
+            assert_eq!(normalize(&code).unwrap(), code);
+        }
+    }
+
+    #[test]
+    fn test_normalize_catches_typos_and_swaps() {
+        let code = recovery_code(&mut OsRng).replace('-', "");
+        let chars = code.chars().collect::<Vec<char>>();
+
+        for i in 0..chars.len() {
+            for replacement in ALPHABET.chars().filter(|c| *c != chars[i]) {
+                let mut typo = chars.clone();
+                typo[i] = replacement;
+                assert!(!is_valid(&typo.iter().collect::<String>()));
+            }
+            for j in (i + 1)..chars.len() {
+                if chars[i] != chars[j] {
+                    let mut swap = chars.clone();
+                    swap.swap(i, j);
+                    assert!(!is_valid(&swap.iter().collect::<String>()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_recovery_set_round_trip() {
+        let mut set = RecoverySet {
+            codes: (0..3)
+                .map(|_| RecoveryCode {
+                    code: recovery_code(&mut OsRng),
+                    used: false,
+                })
+                .collect(),
+        };
+        let code = set.codes[1].code.clone();
+
+        assert_eq!(set.redeem(&code).unwrap(), 2);
+        assert_eq!(RecoverySet::decode(Some(&set.encode())).unwrap(), set);
+        assert_eq!(set.redeem(&code).unwrap_err().code, InvalidRecoveryCode);
+        assert!(RecoverySet::decode(Some("not a code\n")).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use rand::{rngs::OsRng, CryptoRng, Error, RngCore};
+
+/// Name of the variable holding the seed of the reproducible mode, only read with the `seeded-rng` feature.
+pub static SEED_VAR: &str = "MOKUBA_SEED";
+
+/**
+The random number generator used by `gen` and the commands. It is always the OS CSPRNG, unless mokuba is built
+with the `seeded-rng` feature and `MOKUBA_SEED` is set, in which case a ChaCha20 stream seeded from it is used
+so the output is reproducible. The seeded mode is meant for tests only.
+*/
+pub enum DefaultRng {
+    Os(OsRng),
+    #[cfg(feature = "seeded-rng")]
+    Seeded(Box<rand_chacha::ChaCha20Rng>),
+}
+
+impl DefaultRng {
+    /// Returns a generator seeded from `seed`, the same seed always gives the same stream.
+    #[cfg(feature = "seeded-rng")]
+    pub fn seeded(seed: &str) -> DefaultRng {
+        use rand::SeedableRng;
+
+        // FNV-1a, so that any string can be used as a seed and the stream does not depend on the Rust version
+        let seed = seed.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        DefaultRng::Seeded(Box::new(rand_chacha::ChaCha20Rng::seed_from_u64(seed)))
+    }
+}
+
+/// Returns the generator described by `DefaultRng`.
+pub fn default_rng() -> DefaultRng {
+    rng_from(std::env::var(SEED_VAR).ok().as_deref())
+}
+
+/// Returns the generator described by `DefaultRng` for the value of `MOKUBA_SEED`, if set.
+pub fn rng_from(seed: Option<&str>) -> DefaultRng {
+    #[cfg(feature = "seeded-rng")]
+    if let Some(seed) = seed {
+        return DefaultRng::seeded(seed);
+    }
+    #[cfg(not(feature = "seeded-rng"))]
+    let _ = seed;
+
+    DefaultRng::Os(OsRng)
+}
+
+impl RngCore for DefaultRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            DefaultRng::Os(rng) => rng.next_u32(),
+            #[cfg(feature = "seeded-rng")]
+            DefaultRng::Seeded(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            DefaultRng::Os(rng) => rng.next_u64(),
+            #[cfg(feature = "seeded-rng")]
+            DefaultRng::Seeded(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            DefaultRng::Os(rng) => rng.fill_bytes(dest),
+            #[cfg(feature = "seeded-rng")]
+            DefaultRng::Seeded(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        match self {
+            DefaultRng::Os(rng) => rng.try_fill_bytes(dest),
+            #[cfg(feature = "seeded-rng")]
+            DefaultRng::Seeded(rng) => rng.try_fill_bytes(dest),
+        }
+    }
+}
+
+impl CryptoRng for DefaultRng {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng_is_the_os_rng_without_seed() {
+        assert!(matches!(rng_from(None), DefaultRng::Os(_)));
+    }
+
+    #[cfg(not(feature = "seeded-rng"))]
+    #[test]
+    fn test_seed_is_ignored_without_the_feature() {
+        assert!(matches!(rng_from(Some("42")), DefaultRng::Os(_)));
+    }
+
+    #[cfg(feature = "seeded-rng")]
+    #[test]
+    fn test_rng_is_seeded_with_a_seed() {
+        assert!(matches!(rng_from(Some("42")), DefaultRng::Seeded(_)));
+    }
+
+    #[cfg(feature = "seeded-rng")]
+    #[test]
+    fn test_seeded_rng_is_reproducible() {
+        let mut a = DefaultRng::seeded("42");
+        let mut b = DefaultRng::seeded("42");
+        let mut c = DefaultRng::seeded("43");
+        let first = a.next_u64();
+
+        assert_eq!(first, b.next_u64());
+        assert_ne!(first, c.next_u64());
+    }
+}
@@ -0,0 +1,224 @@
+use rand::{CryptoRng, RngCore};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::mstd::{
+    encoding::{
+        base32_encode, base62_encode, base64url_encode, crc32, crockford_encode, hex_encode, BASE62,
+    },
+    error::{
+        DecodeError,
+        ErrorCode::{InvalidChecksum, LengthMustBeGreaterThanZero},
+        GenError,
+    },
+    param::TokenFormat,
+};
+
+// Base62 characters of the CRC32 at the end of a prefixed token, 62^6 > 2^32.
+const CHECKSUM_LENGTH: usize = 6;
+
+/**
+
+## Token Generator
+
+### Examples
+```
+use mcore::{generator::token::{token, verify}, mstd::param::TokenFormat};
+use rand::rngs::OsRng;
+
+assert_eq!(token(&TokenFormat::Hex, 32, &mut OsRng).unwrap().len(), 64);
+assert_eq!(token(&TokenFormat::Uuid, 0, &mut OsRng).unwrap().len(), 36);
+
+let api = token(&TokenFormat::Prefixed("ghp".to_string()), 22, &mut OsRng).unwrap();
+assert_eq!(api.len(), 40);
+assert_eq!(verify(&api).unwrap(), "ghp");
+```
+
+Generates a machine secret of `bytes` random bytes in the given encoding. UUIDs and ULIDs have a fixed size
+and ignore `bytes`.
+
+This function will return an error if `bytes` is 0 for the formats that use it.
+*/
+pub fn token<R: RngCore + CryptoRng>(
+    format: &TokenFormat,
+    bytes: u32,
+    rng: &mut R,
+) -> Result<String, GenError> {
+    let random = |rng: &mut R| -> Result<Vec<u8>, GenError> {
+        if bytes == 0 {
+            return Err(GenError::new(
+                LengthMustBeGreaterThanZero,
+                "Please choose an amount of bytes greater than 0".to_owned(),
+            ));
+        }
+
+        let mut random = vec![0u8; bytes as usize];
+        rng.fill_bytes(&mut random);
+        Ok(random)
+    };
+
+    match format {
+        TokenFormat::Hex => Ok(hex_encode(&random(rng)?)),
+        TokenFormat::Base32 => Ok(base32_encode(&random(rng)?)),
+        TokenFormat::Base64Url => Ok(base64url_encode(&random(rng)?)),
+        TokenFormat::Uuid => Ok(uuid(rng)),
+        TokenFormat::Ulid => Ok(ulid(now(), rng)),
+        TokenFormat::Prefixed(prefix) => {
+            let random = random(rng)?;
+            let body = base62_encode(&random, base62_width(random.len()));
+            Ok(format!("{}_{}{}", prefix, body, checksum(&body)))
+        }
+    }
+}
+
+/// A random UUID, version 4, in its hyphenated lowercase form.
+pub fn uuid<R: RngCore + CryptoRng>(rng: &mut R) -> String {
+    let mut bytes = [0u8; 16];
+    rng.fill_bytes(&mut bytes);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = hex_encode(&bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// A ULID for `time`, in milliseconds since the Unix epoch. ULIDs of different milliseconds sort by time.
+pub fn ulid<R: RngCore + CryptoRng>(time: u64, rng: &mut R) -> String {
+    let mut random = [0u8; 16];
+    rng.fill_bytes(&mut random[6..]);
+
+    let time = (time & 0xffff_ffff_ffff) as u128;
+    crockford_encode((time << 80) | u128::from_be_bytes(random))
+}
+
+/**
+Checks the CRC32 suffix of a prefixed token and returns its prefix, so mistyped or truncated tokens are
+rejected without looking them up.
+
+This function will return an error if the token has no prefix, is not base62 or if its checksum does not match.
+*/
+pub fn verify(token: &str) -> Result<&str, DecodeError> {
+    let (prefix, rest) = token
+        .rsplit_once('_')
+        .filter(|(prefix, _)| !prefix.is_empty())
+        .ok_or_else(|| {
+            DecodeError::new(
+                InvalidChecksum,
+                format!("The token {} has no <prefix>_ in front", token),
+            )
+        })?;
+
+    if rest.len() <= CHECKSUM_LENGTH || !rest.bytes().all(|b| BASE62.contains(&b)) {
+        return Err(DecodeError::new(
+            InvalidChecksum,
+            format!("The token {} is not a prefixed base62 token", token),
+        ));
+    }
+
+    let (body, sum) = rest.split_at(rest.len() - CHECKSUM_LENGTH);
+    match checksum(body) == sum {
+        true => Ok(prefix),
+        false => Err(DecodeError::new(
+            InvalidChecksum,
+            format!("The checksum of the token {} does not match", token),
+        )),
+    }
+}
+
+fn checksum(body: &str) -> String {
+    base62_encode(&crc32(body.as_bytes()).to_be_bytes(), CHECKSUM_LENGTH)
+}
+
+// Base62 characters needed for any number of `bytes` bytes.
+fn base62_width(bytes: usize) -> usize {
+    (bytes as f64 * 8.0 / 62f64.log2()).ceil() as usize
+}
+
+fn now() -> u64 {
+    // A clock before 1970 is treated as the epoch, the ULID stays valid.
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_token_encodes_the_amount_of_bytes() {
+        let lengths = [
+            (TokenFormat::Hex, 64),
+            (TokenFormat::Base32, 52),
+            (TokenFormat::Base64Url, 43),
+            (
+                TokenFormat::Prefixed("mo".to_string()),
+                3 + 43 + CHECKSUM_LENGTH,
+            ),
+        ];
+
+        for (format, length) in lengths {
+            assert_eq!(token(&format, 32, &mut OsRng).unwrap().len(), length);
+            assert_eq!(
+                token(&format, 0, &mut OsRng).unwrap_err().code,
+                LengthMustBeGreaterThanZero
+            );
+        }
+    }
+
+    #[test]
+    fn test_uuid_is_version_4() {
+        let uuid = uuid(&mut OsRng);
+        let groups = uuid.split('-').map(|g| g.len()).collect::<Vec<usize>>();
+
+        assert_eq!(groups, [8, 4, 4, 4, 12]);
+        assert_eq!(&uuid[14..15], "4");
+        assert!("89ab".contains(&uuid[19..20]));
+    }
+
+    #[test]
+    fn test_ulid_sorts_by_time() {
+        let first = ulid(1_469_918_176_385, &mut OsRng);
+        let second = ulid(1_469_918_176_386, &mut OsRng);
+
+        assert_eq!(first.len(), 26);
+        // Timestamp example of the ULID specification
+        assert_eq!(&first[..10], "01ARYZ6S41");
+        assert!(first < second);
+    }
+
+    #[test]
+    fn test_verify_rejects_altered_tokens() {
+        let token = token(&TokenFormat::Prefixed("ghp".to_string()), 22, &mut OsRng).unwrap();
+        assert_eq!(verify(&token).unwrap(), "ghp");
+
+        let last = token.chars().last().unwrap();
+        let typo = format!(
+            "{}{}",
+            &token[..token.len() - 1],
+            if last == 'a' { 'b' } else { 'a' }
+        );
+        for invalid in [
+            typo.as_str(),
+            &token[..token.len() - 1],
+            &token[4..],
+            "ghp_",
+            "ghp_abc!defghij",
+        ] {
+            assert_eq!(
+                verify(invalid).unwrap_err().code,
+                InvalidChecksum,
+                "{}",
+                invalid
+            );
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use rand::{seq::SliceRandom, CryptoRng, Rng, RngCore};
+
+use crate::mstd::{
+    error::{ErrorCode::LengthMustBeGreaterThanZero, GenError},
+    param::{Username, DIGITS, LOWER},
+};
+
+static ADJECTIVES: &[&str] = &[
+    "able", "agile", "amber", "ample", "azure", "bold", "brave", "breezy", "bright", "brisk",
+    "calm", "candid", "cheery", "civil", "clever", "cosmic", "cozy", "crisp", "curious", "daring",
+    "dapper", "eager", "early", "earnest", "easy", "epic", "fair", "fancy", "fast", "fearless",
+    "fluffy", "fond", "frank", "fresh", "gentle", "giant", "glad", "golden", "grand", "happy",
+    "hardy", "hearty", "honest", "humble", "icy", "jolly", "jovial", "keen", "kind", "lively",
+    "lucky", "lunar", "mellow", "merry", "mighty", "modest", "neat", "nimble", "noble", "odd",
+    "plucky", "polite", "proud", "quick", "quiet", "rapid", "rosy", "royal", "rustic", "sage",
+    "savvy", "shiny", "silent", "simple", "sleek", "smart", "snappy", "snowy", "solar", "spicy",
+    "steady", "stellar", "sturdy", "sunny", "swift", "tidy", "tiny", "tranquil", "trusty",
+    "upbeat", "urban", "valiant", "vivid", "warm", "wise", "witty", "zany", "zealous", "zen",
+    "zesty",
+];
+
+static NOUNS: &[&str] = &[
+    "acorn", "anchor", "badger", "beacon", "bison", "breeze", "brook", "cactus", "canyon",
+    "castle", "cedar", "comet", "coral", "cougar", "crane", "cricket", "dolphin", "dragon",
+    "eagle", "ember", "falcon", "fern", "finch", "fjord", "forest", "fox", "galaxy", "garnet",
+    "gecko", "glacier", "harbor", "hawk", "heron", "island", "jaguar", "jasper", "kayak",
+    "kestrel", "koala", "lagoon", "lantern", "lemur", "lotus", "lynx", "maple", "meadow", "meteor",
+    "mesa", "moose", "nebula", "newt", "oasis", "ocelot", "orchid", "otter", "owl", "panda",
+    "pebble", "pelican", "pine", "planet", "puffin", "quartz", "quokka", "rabbit", "raven", "reef",
+    "river", "robin", "sage", "salmon", "sparrow", "spruce", "squid", "stone", "summit", "swan",
+    "thistle", "tiger", "tulip", "tundra", "turtle", "valley", "violet", "walrus", "willow",
+    "wolf", "wombat", "yak", "zebra",
+];
+
+// Length of the usernames of the random style.
+const RANDOM_LENGTH: usize = 12;
+
+/**
+
+## Username Generator
+
+### Examples
+```
+use mcore::{generator::username::username, mstd::param::Username};
+use rand::rngs::OsRng;
+
+let name = username(&Username::Words, &mut OsRng).unwrap();
+assert_eq!(name.split('-').count(), 3);
+
+let alias = username(&Username::Email("example.com".to_string()), &mut OsRng).unwrap();
+assert!(alias.ends_with("@example.com"));
+```
+
+Generates a username in the given style: `adjective-noun-number`, random lowercase letters and digits starting
+with a letter, or an email alias like `adjective.noun.number@domain`. A fixed username is returned as-is.
+
+This function will return an error if a fixed username or the domain of an email alias is empty.
+*/
+pub fn username<R: RngCore + CryptoRng>(style: &Username, rng: &mut R) -> Result<String, GenError> {
+    let words = |rng: &mut R, separator: &str| {
+        let adjective = ADJECTIVES.choose(rng).copied().unwrap_or_default();
+        let noun = NOUNS.choose(rng).copied().unwrap_or_default();
+        format!(
+            "{adjective}{separator}{noun}{separator}{}",
+            rng.gen_range(0..10000)
+        )
+    };
+
+    match style {
+        Username::Words => Ok(words(rng, "-")),
+        Username::Random => {
+            let first = LOWER.chars().collect::<Vec<char>>();
+            let rest = format!("{}{}", LOWER, DIGITS)
+                .chars()
+                .collect::<Vec<char>>();
+
+            Ok(first
+                .choose(rng)
+                .into_iter()
+                .chain((1..RANDOM_LENGTH).filter_map(|_| rest.choose(rng)))
+                .collect())
+        }
+        Username::Email(domain) => match domain.trim_start_matches('@') {
+            "" => Err(GenError::new(
+                LengthMustBeGreaterThanZero,
+                "Please choose a domain for the email alias".to_owned(),
+            )),
+            domain => Ok(format!("{}@{}", words(rng, "."), domain.to_lowercase())),
+        },
+        Username::Fixed(name) => match name.is_empty() {
+            true => Err(GenError::new(
+                LengthMustBeGreaterThanZero,
+                "Please choose a non empty username".to_owned(),
+            )),
+            false => Ok(name.to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_words_username() {
+        let name = username(&Username::Words, &mut OsRng).unwrap();
+        let parts = name.split('-').collect::<Vec<&str>>();
+
+        assert!(ADJECTIVES.contains(&parts[0]));
+        assert!(NOUNS.contains(&parts[1]));
+        assert!(parts[2].parse::<u32>().unwrap() < 10000);
+    }
+
+    #[test]
+    fn test_random_username() {
+        let name = username(&Username::Random, &mut OsRng).unwrap();
+
+        assert_eq!(name.len(), RANDOM_LENGTH);
+        assert!(name.starts_with(|c: char| c.is_ascii_lowercase()));
+        assert!(name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn test_email_username() {
+        let name = username(&Username::Email("@QA.Example.com".to_string()), &mut OsRng).unwrap();
+        let (local, domain) = name.split_once('@').unwrap();
+
+        assert_eq!(domain, "qa.example.com");
+        assert_eq!(local.split('.').count(), 3);
+        assert!(username(&Username::Email(String::new()), &mut OsRng).is_err());
+    }
+
+    #[test]
+    fn test_fixed_username() {
+        let name = username(&Username::Fixed("test".to_string()), &mut OsRng).unwrap();
+        assert_eq!(name, "test");
+    }
+}
@@ -1,14 +1,24 @@
 pub mod algebra;
+pub mod audit;
+pub mod cert;
+pub mod generator;
 pub mod mstd;
 pub mod syntax;
+pub mod vault;
 
 use mstd::{
     error::{
-        ErrorCode::{LengthMustBeGreaterThanZero, UnableToConvertNumberToChar},
+        ErrorCode::{LengthMustBeGreaterThanZero, PolicyCannotBeSatisfied},
         GenError,
     },
-    param::GenerateParams,
+    param::{GenerateParams, Mode},
 };
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    CryptoRng, Rng, RngCore,
+};
+use unicode_normalization::UnicodeNormalization;
+
 /**
 
 ## Password Generator
@@ -16,86 +26,618 @@ use mstd::{
 ### Examples
 ```
 use mcore::{gen, mstd::param::GenerateParams};
-let password = gen(GenerateParams { length: 10, username: None });
+let password = gen(GenerateParams { length: 10, ..Default::default() });
 ```
 
-This function will return an error if the length is less than 1 or if the acc seed is different than an empty string.
+Every password contains at least the minimum amount of characters of each class in `params.policy` and
+only characters that the policy allows. Any other `params.mode` is handed over to its generator. When
+`params.min_entropy` is set, the length is derived from it. Passphrases, PINs, tokens and recovery codes
+have fixed alphabets, so they cannot be restricted to keyboard layouts.
+
+Randomness comes from `generator::rng::default_rng`, use `gen_with_rng` to provide another generator.
+
+This function will return an error if the length is less than 1, if the policy cannot be satisfied with the
+given length or if layouts are given to a mode with a fixed alphabet.
 */
 pub fn gen(params: GenerateParams) -> Result<String, GenError> {
-    gen_with_seed(params, "")
+    gen_with_rng(params, &mut generator::rng::default_rng())
 }
 
 /**
 
-### Arguments
+## Credentials Generator
 
-* `params` - `GenerateParams` struct containing the length of the password to generate
-* `seed` - `String` containing the seed to use for the password generation
+### Examples
+```
+use mcore::{gen_credentials, mstd::param::{GenerateParams, Username}};
 
-### Returns
-This function will return an error if the length is less than 1 or if the acc seed is different than an empty string.
+let (username, password) = gen_credentials(GenerateParams::new(16, Some(Username::Words))).unwrap();
+assert_eq!(username.unwrap().split('-').count(), 3);
+assert_eq!(password.chars().count(), 16);
+```
 
+Generates the username described by `params.username`, if any, along with the password `gen` would return.
 */
-fn gen_with_seed(params: GenerateParams, acc: &str) -> Result<String, GenError> {
-    use rand::{thread_rng, Rng};
-    use std::char::from_u32;
+pub fn gen_credentials(params: GenerateParams) -> Result<(Option<String>, String), GenError> {
+    gen_credentials_with_rng(params, &mut generator::rng::default_rng())
+}
+
+/// Works like `gen_credentials`, drawing every random choice from `rng`.
+pub fn gen_credentials_with_rng<R: RngCore + CryptoRng>(
+    params: GenerateParams,
+    rng: &mut R,
+) -> Result<(Option<String>, String), GenError> {
+    let username = params
+        .username
+        .as_ref()
+        .map(|style| generator::username::username(style, rng))
+        .transpose()?;
+
+    Ok((username, gen_with_rng(params, rng)?))
+}
+
+/**
+
+## Bulk Password Generator
+
+### Examples
+```
+use mcore::{gen_iter, mstd::param::GenerateParams};
+use rand::rngs::OsRng;
+
+let passwords = gen_iter(GenerateParams::default(), OsRng)
+    .take(1000)
+    .collect::<Result<Vec<String>, _>>()
+    .unwrap();
+assert_eq!(passwords.len(), 1000);
+```
+
+Lazily generates secrets with the same params, so any amount of them can be streamed without holding them
+in memory.
+*/
+pub fn gen_iter<R: RngCore + CryptoRng>(
+    params: GenerateParams,
+    mut rng: R,
+) -> impl Iterator<Item = Result<String, GenError>> {
+    std::iter::repeat_with(move || gen_with_rng(params.clone(), &mut rng))
+}
+
+/**
+
+## Password Generator with a custom random number generator
+
+### Examples
+```
+use mcore::{gen_with_rng, mstd::param::GenerateParams};
+use rand::{rngs::StdRng, SeedableRng};
+
+let first = gen_with_rng(GenerateParams::default(), &mut StdRng::seed_from_u64(42));
+let second = gen_with_rng(GenerateParams::default(), &mut StdRng::seed_from_u64(42));
+assert_eq!(first.unwrap(), second.unwrap());
+```
+
+Works like `gen`, drawing every random choice from `rng`.
+*/
+pub fn gen_with_rng<R: RngCore + CryptoRng>(
+    params: GenerateParams,
+    rng: &mut R,
+) -> Result<String, GenError> {
+    let fixed = match &params.mode {
+        Mode::Passphrase(_) => Some("passphrase"),
+        Mode::Pin => Some("pin"),
+        Mode::Token(_) => Some("token"),
+        Mode::Recovery => Some("recovery"),
+        Mode::Password | Mode::Pronounceable | Mode::Pattern(_) => None,
+    };
+    if let Some(mode) = fixed.filter(|_| !params.policy.layouts.is_empty()) {
+        return Err(GenError::new(
+            PolicyCannotBeSatisfied,
+            format!(
+                "The {} mode has a fixed alphabet and cannot be limited to keyboard layouts",
+                mode
+            ),
+        ));
+    }
 
-    if params.length == 0 {
-        if acc.is_empty() {
+    let params = generator::entropy::fit(params)?;
+
+    match &params.mode {
+        Mode::Password => draw_password(params, rng),
+        Mode::Passphrase(passphrase) => generator::passphrase::passphrase(passphrase, rng),
+        Mode::Pronounceable => generator::pronounceable::pronounceable(&params, rng),
+        Mode::Pattern(pattern) => {
+            generator::pattern::pattern(pattern, &params.policy.excluded(), rng)
+        }
+        Mode::Pin => generator::pin::pin(params.length, rng),
+        Mode::Token(format) => generator::token::token(format, params.length, rng),
+        Mode::Recovery => Ok(generator::recovery::recovery_code(rng)),
+    }
+}
+
+// Symbols are drawn in order. Every position is drawn from a class that still misses symbols with the probability
+// of its missing symbols among the positions left, and from the whole alphabet otherwise. The maximum amount of
+// identical consecutive characters is enforced while drawing, by leaving out the repeated symbol.
+fn draw_password<R: RngCore + CryptoRng>(
+    params: GenerateParams,
+    rng: &mut R,
+) -> Result<String, GenError> {
+    let required = params.policy.required();
+    if params.length < required {
+        return Err(GenError::new(
+            PolicyCannotBeSatisfied,
+            format!(
+                "The policy requires at least {} characters but the length is {}",
+                required, params.length
+            ),
+        ));
+    }
+
+    let mut classes = Vec::new();
+    let mut missing = Vec::new();
+    for rule in params.policy.rules.iter().filter(|rule| rule.min > 0) {
+        let symbols = params.policy.symbols_of(&rule.class);
+        if symbols.is_empty() {
             return Err(GenError::new(
-                LengthMustBeGreaterThanZero,
-                "Please choose a length greater than 0".to_owned(),
+                PolicyCannotBeSatisfied,
+                format!(
+                    "No characters left in class {:?} after exclusions",
+                    rule.class
+                ),
             ));
+        }
+        classes.push(symbols);
+        missing.push(rule.min);
+    }
+
+    let alphabet = params.policy.symbols();
+    let max_consecutive = params.policy.max_consecutive;
+    let mut symbols = Vec::with_capacity(params.length as usize);
+
+    while missing.iter().any(|n| *n > 0) {
+        let left = params.length - symbols.len() as u32;
+        let pick = rng.gen_range(0..left);
+        let class = missing
+            .iter()
+            .scan(0, |sum, n| {
+                *sum += n;
+                Some(*sum)
+            })
+            .position(|sum| pick < sum);
+
+        let drawn = class.and_then(|i| draw_next(&classes[i], &symbols, max_consecutive, rng));
+        let forced = missing.iter().sum::<u32>() == left;
+        let symbol = match (drawn, forced) {
+            (Some(symbol), _) => symbol,
+            (None, false) => draw_next(&alphabet, &symbols, max_consecutive, rng)
+                .ok_or_else(|| too_many_consecutive(&params))?,
+            (None, true) => return Err(too_many_consecutive(&params)),
         };
-        return Ok(acc.to_string());
+
+        for (class, missing) in classes.iter().zip(missing.iter_mut()) {
+            if *missing > 0 && class.binary_search(&symbol).is_ok() {
+                *missing -= 1;
+            }
+        }
+        symbols.push(symbol);
     }
 
-    let codepoint = thread_rng().gen_range(48..122);
+    let left = params.length - symbols.len() as u32;
+    let symbols = gen_with_seed(with_length(&params, left), symbols, rng)?;
 
-    match from_u32(codepoint) {
-        Some(value) => gen_with_seed(
-            GenerateParams::new(params.length - 1, None),
-            &format!("{}{}", acc, value),
-        ),
-        None => Err(GenError::new(
-            UnableToConvertNumberToChar,
-            format!("Unable to convert codepoint:{} to char", codepoint),
-        )),
+    match params.policy.nfc {
+        // Every symbol is already in NFC and none composes with the one before it, this only guarantees it.
+        true => Ok(symbols.concat().nfc().collect()),
+        false => Ok(symbols.concat()),
+    }
+}
+
+/**
+
+### Arguments
+
+* `params` - `GenerateParams` struct containing the amount of symbols to draw and the policy to draw from
+* `acc` - symbols already drawn, the new ones are appended to them
+* `rng` - random number generator every symbol is drawn from
+
+### Returns
+This function will return an error if the length is less than 1 and `acc` is empty.
+
+*/
+fn gen_with_seed<R: RngCore + CryptoRng>(
+    params: GenerateParams,
+    acc: Vec<String>,
+    rng: &mut R,
+) -> Result<Vec<String>, GenError> {
+    if params.length == 0 && acc.is_empty() {
+        return Err(GenError::new(
+            LengthMustBeGreaterThanZero,
+            "Please choose a length greater than 0".to_owned(),
+        ));
+    }
+
+    let alphabet = params.policy.symbols();
+    let mut password = acc;
+    password.reserve(params.length as usize);
+
+    for _ in 0..params.length {
+        match draw_next(&alphabet, &password, params.policy.max_consecutive, rng) {
+            Some(value) => password.push(value),
+            None if alphabet.is_empty() => {
+                return Err(GenError::new(
+                    PolicyCannotBeSatisfied,
+                    "The policy does not allow any character".to_owned(),
+                ))
+            }
+            None => return Err(too_many_consecutive(&params)),
+        }
     }
+
+    Ok(password)
+}
+
+// Draws the symbol that follows `password` from `symbols`, leaving out the last symbol of `password` once it is
+// repeated `max_consecutive` times in a row.
+fn draw_next<R: RngCore + CryptoRng>(
+    symbols: &[String],
+    password: &[String],
+    max_consecutive: Option<u32>,
+    rng: &mut R,
+) -> Option<String> {
+    let repeated = match (max_consecutive, password.last()) {
+        (Some(max), Some(last))
+            if password.iter().rev().take_while(|s| *s == last).count() >= max as usize =>
+        {
+            last
+        }
+        _ => return symbols.choose(rng).cloned(),
+    };
+
+    symbols
+        .iter()
+        .filter(|symbol| *symbol != repeated)
+        .choose(rng)
+        .cloned()
+}
+
+fn with_length(params: &GenerateParams, length: u32) -> GenerateParams {
+    GenerateParams {
+        length,
+        ..params.clone()
+    }
+}
+
+fn too_many_consecutive(params: &GenerateParams) -> GenError {
+    let max = params.policy.max_consecutive.unwrap_or_default();
+    GenError::new(
+        PolicyCannotBeSatisfied,
+        format!(
+            "Unable to avoid more than {} identical consecutive characters",
+            max
+        ),
+    )
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{gen_with_seed, GenerateParams, LengthMustBeGreaterThanZero};
+    use crate::{
+        gen, gen_iter, gen_with_rng, gen_with_seed,
+        mstd::{
+            layout::{is_safe, Layout},
+            param::{CharClass, ClassRule, Mode, Passphrase, Policy, TokenFormat},
+        },
+        GenerateParams, LengthMustBeGreaterThanZero, PolicyCannotBeSatisfied,
+    };
+    use rand::{
+        rngs::{OsRng, StdRng},
+        SeedableRng,
+    };
 
     #[test]
     fn produces_a_password_of_10_digits() {
-        let result = gen_with_seed(GenerateParams::new(10, None), "");
+        let result = gen_with_seed(GenerateParams::new(10, None), vec![], &mut OsRng);
         assert_eq!(result.unwrap().len(), 10);
     }
 
     #[test]
     fn produces_a_password_of_20_digits() {
-        let result = gen_with_seed(GenerateParams::new(20, None), "");
+        let result = gen_with_seed(GenerateParams::new(20, None), vec![], &mut OsRng);
         assert_eq!(result.unwrap().len(), 20);
     }
 
+    #[test]
+    fn produces_a_long_password_without_overflowing_the_stack() {
+        let result = gen_with_seed(GenerateParams::new(1_000_000, None), vec![], &mut OsRng);
+        assert_eq!(result.unwrap().len(), 1_000_000);
+    }
+
+    #[test]
+    fn produces_many_different_passwords() {
+        let passwords = gen_iter(GenerateParams::new(16, None), OsRng)
+            .take(100)
+            .collect::<Result<std::collections::HashSet<String>, _>>()
+            .unwrap();
+        assert_eq!(passwords.len(), 100);
+    }
+
     #[test]
     fn produces_a_password_of_30_digits() {
-        let result = gen_with_seed(GenerateParams::new(30, None), "");
+        let result = gen_with_seed(GenerateParams::new(30, None), vec![], &mut OsRng);
         assert_eq!(result.unwrap().len(), 30);
     }
 
     #[test]
     fn produces_an_error_when_length_is_less_than_1() {
-        let result = gen_with_seed(GenerateParams::new(0, None), "");
+        let result = gen_with_seed(GenerateParams::new(0, None), vec![], &mut OsRng);
         assert!(result.unwrap_err().code == LengthMustBeGreaterThanZero);
     }
 
     #[test]
     fn produces_a_password_prepended_with_a_seed() {
-        let test_seed = "test_seed";
-        let result = gen_with_seed(GenerateParams::new(15, None), test_seed);
-        assert!(result.unwrap().len() == 15 + test_seed.len());
+        let test_seed = vec!["test".to_string(), "_".to_string(), "seed".to_string()];
+        let result = gen_with_seed(GenerateParams::new(15, None), test_seed, &mut OsRng).unwrap();
+        assert_eq!(result.len(), 15 + 3);
+        assert_eq!(result[..3].concat(), "test_seed");
+    }
+
+    #[test]
+    fn produces_a_password_of_unicode_characters() {
+        let policy = |graphemes, nfc| Policy {
+            graphemes,
+            nfc,
+            ..Policy::new(
+                vec![ClassRule::new(
+                    CharClass::Custom("日本語e\u{301}あ".to_string()),
+                    1,
+                )],
+                String::new(),
+            )
+        };
+        let password = |policy| {
+            gen(GenerateParams {
+                length: 12,
+                policy,
+                ..Default::default()
+            })
+            .unwrap()
+        };
+
+        let scalars = password(policy(false, false));
+        assert_eq!(scalars.chars().count(), 12);
+
+        let graphemes = password(policy(true, false));
+        assert_eq!(graphemes.chars().filter(|c| *c != '\u{301}').count(), 12);
+
+        let normalized = password(policy(true, true));
+        assert_eq!(normalized.chars().count(), 12);
+        assert!(normalized.chars().all(|c| "日本語éあ".contains(c)));
+    }
+
+    #[test]
+    fn produces_a_normalized_password_of_the_set_without_graphemes() {
+        let policy = Policy {
+            nfc: true,
+            ..Policy::new(
+                vec![ClassRule::new(
+                    CharClass::Custom("\u{301}e\u{301}a".to_string()),
+                    1,
+                )],
+                String::new(),
+            )
+        };
+        assert_eq!(policy.symbols(), ["a", "é"]);
+
+        for _ in 0..20 {
+            let password = gen(GenerateParams {
+                length: 10,
+                policy: policy.clone(),
+                ..Default::default()
+            })
+            .unwrap();
+
+            assert_eq!(password.chars().count(), 10, "{}", password);
+            assert!(password.chars().all(|c| "aé".contains(c)), "{}", password);
+            assert!(policy.is_satisfied_by(&password));
+        }
+        assert!(!policy.is_satisfied_by("\u{301}aéééééééé"));
+    }
+
+    #[test]
+    fn produces_an_error_when_layouts_are_given_to_a_fixed_alphabet() {
+        let policy = Policy {
+            layouts: vec![Layout::Mobile],
+            ..Default::default()
+        };
+
+        for mode in [
+            Mode::Passphrase(Passphrase::default()),
+            Mode::Pin,
+            Mode::Token(TokenFormat::Hex),
+            Mode::Recovery,
+        ] {
+            let result = gen(GenerateParams {
+                policy: policy.clone(),
+                mode,
+                ..Default::default()
+            });
+            assert_eq!(result.unwrap_err().code, PolicyCannotBeSatisfied);
+        }
+    }
+
+    #[test]
+    fn produces_a_password_typed_the_same_way_on_every_layout() {
+        let layouts = vec![Layout::Azerty, Layout::Qwertz];
+        let policy = Policy {
+            layouts: layouts.clone(),
+            ..Policy::new(
+                vec![
+                    ClassRule::new(CharClass::Lower, 1),
+                    ClassRule::new(CharClass::Digits, 1),
+                ],
+                String::new(),
+            )
+        };
+
+        for mode in [Mode::Password, Mode::Pronounceable] {
+            let password = gen(GenerateParams {
+                length: 32,
+                policy: policy.clone(),
+                mode,
+                ..Default::default()
+            })
+            .unwrap();
+
+            assert!(
+                password.chars().all(|c| is_safe(&layouts, c)),
+                "{}",
+                password
+            );
+        }
+    }
+
+    #[test]
+    fn produces_a_password_that_satisfies_the_policy() {
+        let policy = Policy::new(
+            vec![
+                ClassRule::new(CharClass::Upper, 2),
+                ClassRule::new(CharClass::Digits, 3),
+                ClassRule::new(CharClass::Custom("!#$%".to_string()), 1),
+            ],
+            "O0".to_string(),
+        );
+
+        for _ in 0..50 {
+            let password = gen(GenerateParams {
+                length: 8,
+                policy: policy.clone(),
+                ..Default::default()
+            })
+            .unwrap();
+
+            assert_eq!(password.chars().count(), 8);
+            assert!(policy.is_satisfied_by(&password));
+            assert!(!password.contains('O') && !password.contains('0'));
+        }
+    }
+
+    #[test]
+    fn produces_a_password_long_enough_for_the_min_entropy() {
+        let result = gen(GenerateParams {
+            length: 1,
+            min_entropy: Some(128),
+            ..Default::default()
+        });
+        assert_eq!(result.unwrap().chars().count(), 20);
+    }
+
+    #[test]
+    fn produces_the_same_secrets_with_the_same_rng() {
+        let modes = [
+            Mode::Password,
+            Mode::Passphrase(Passphrase::default()),
+            Mode::Pronounceable,
+            Mode::Pattern("Aaaa-9999".to_string()),
+            Mode::Pin,
+            Mode::Token(TokenFormat::Prefixed("mo".to_string())),
+            Mode::Recovery,
+        ];
+
+        for mode in modes {
+            let params = || GenerateParams {
+                length: 16,
+                mode: mode.clone(),
+                ..Default::default()
+            };
+            let first = gen_with_rng(params(), &mut StdRng::seed_from_u64(7)).unwrap();
+            let second = gen_with_rng(params(), &mut StdRng::seed_from_u64(7)).unwrap();
+            let other = gen_with_rng(params(), &mut StdRng::seed_from_u64(8)).unwrap();
+
+            assert_eq!(first, second);
+            assert_ne!(first, other);
+        }
+    }
+
+    #[test]
+    fn produces_a_password_without_too_many_consecutive_characters() {
+        let policy = Policy {
+            max_consecutive: Some(1),
+            ..Policy::new(
+                vec![ClassRule::new(CharClass::Custom("ab".to_string()), 0)],
+                String::new(),
+            )
+        };
+        let password = gen(GenerateParams {
+            length: 6,
+            policy,
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(password == "ababab" || password == "bababa");
+    }
+
+    #[test]
+    fn produces_passwords_without_too_many_consecutive_characters_at_any_length() {
+        let policy = Policy {
+            max_consecutive: Some(1),
+            ..Policy::new(
+                vec![
+                    ClassRule::new(CharClass::Custom("ab".to_string()), 0),
+                    ClassRule::new(CharClass::Custom("-".to_string()), 2),
+                ],
+                String::new(),
+            )
+        };
+
+        for _ in 0..200 {
+            let password = gen(GenerateParams {
+                length: 64,
+                policy: policy.clone(),
+                ..Default::default()
+            })
+            .unwrap();
+
+            assert_eq!(password.len(), 64);
+            assert!(policy.is_satisfied_by(&password), "{}", password);
+        }
+    }
+
+    #[test]
+    fn produces_an_error_when_the_consecutive_characters_cannot_be_avoided() {
+        let policy = Policy {
+            max_consecutive: Some(2),
+            ..Policy::new(
+                vec![ClassRule::new(CharClass::Custom("a".to_string()), 1)],
+                String::new(),
+            )
+        };
+        let result = gen(GenerateParams {
+            length: 3,
+            policy,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            result.unwrap_err().cause,
+            "Unable to avoid more than 2 identical consecutive characters"
+        );
+    }
+
+    #[test]
+    fn produces_an_error_when_the_minimums_exceed_the_length() {
+        let result = gen(GenerateParams::new(3, None));
+        assert!(result.unwrap_err().code == PolicyCannotBeSatisfied);
+    }
+
+    #[test]
+    fn produces_an_error_when_a_required_class_is_fully_excluded() {
+        let result = gen(GenerateParams {
+            length: 10,
+            policy: Policy::new(
+                vec![ClassRule::new(CharClass::Digits, 1)],
+                "0123456789".to_string(),
+            ),
+            ..Default::default()
+        });
+        assert!(result.unwrap_err().code == PolicyCannotBeSatisfied);
     }
 }
@@ -1,6 +1,6 @@
 use mmacro::ConstructorM;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, io::Write};
 
 pub static VERSION: &str = env!("CARGO_PKG_VERSION");
 pub static BIN_NAME: &str = env!("CARGO_PKG_NAME");
@@ -10,16 +10,22 @@ pub static HELP: &str = concat!(
     " [command] [options]\n",
 );
 
+/// A command, the values of its flags and the values given before the first flag, in order.
 #[derive(Debug, ConstructorM)]
 pub struct Input {
     pub arg: String,
     pub params: HashMap<String, String>,
+    pub values: Vec<String>,
 }
 
 pub trait Execute<P> {
     fn execute(&self, params: Option<P>) -> Result<String, String>;
 }
 
+pub trait Stream<P> {
+    fn stream<W: Write>(&self, params: Option<P>, writer: W) -> Result<(), String>;
+}
+
 pub trait Argument {
     fn short(&self) -> char;
     fn argument(&self) -> String;
@@ -1,79 +1,1781 @@
 use crate::mstd::error::ErrorCode::UnableToParseInputToT;
-use std::collections::HashSet;
+use std::{
+    collections::HashSet,
+    fs,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{cmd::Input, codec::Codec, error::GetInputError, read_line_from, rules::PasswordRules};
+use crate::{
+    audit::{
+        breach::pwned,
+        strength::{display_time, strength},
+    },
+    cert::{
+        authority::{create_ca, issue, load, save, CertifiedKey},
+        bundle::{pem_chain, pkcs12},
+    },
+    gen_credentials_with_rng, gen_with_rng,
+    generator::{
+        derive::derive,
+        entropy::{entropy, fit},
+        keygen::{keypair, write_keypair},
+        otp::{otp, remaining},
+        pin,
+        recovery::RecoverySet,
+        rng::default_rng,
+        token::verify,
+    },
+    vault::{self, entry::Entry, Vault, DEFAULT_HISTORY, KEYFILE_LENGTH},
+};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use super::{
+    cmd::{Argument, Execute, Parse, Stream, HELP},
+    layout::Layout,
+    otpauth::{Algorithm, OtpAuth, OtpKind},
+    param::{
+        BreachParams, BundleFormat, CertAction, CertParams, CharClass, CheckParams, ClassRule,
+        DeriveParams, GenerateParams, Kdf, KeyType, KeyfileParams, KeygenParams, Mode, OtpParams,
+        Passphrase, Policy, RedeemParams, TokenFormat, Username, VaultAction, VaultParams,
+    },
+    store::{create_dir, home, lock, replace_private, write_private},
+};
+
+// Available commands
+#[derive(Debug, Copy, Clone)]
+pub struct Generate;
+
+impl Execute<GenerateParams> for Generate {
+    fn execute(&self, params: Option<GenerateParams>) -> Result<String, String> {
+        let mut output = Vec::new();
+        self.stream(params, &mut output)?;
+        String::from_utf8(output).map_err(|e| e.to_string())
+    }
+}
+
+impl Stream<GenerateParams> for Generate {
+    fn stream<W: Write>(&self, params: Option<GenerateParams>, writer: W) -> Result<(), String> {
+        let params = params.unwrap_or_else(|| GenerateParams::new(16, None));
+        let params = fit(params).map_err(|e| e.cause)?;
+        let bits = entropy(&params);
+        let (count, delimiter) = (params.count as usize, params.delimiter);
+        // Streamed records are bare secrets, so they can be piped as-is.
+        let bulk = count > 1 || delimiter != '\n';
+        let mut writer = BufWriter::new(writer);
+        let mut rng = default_rng();
+
+        for i in 0..count {
+            let (username, secret) =
+                gen_credentials_with_rng(params.clone(), &mut rng).map_err(|e| e.cause)?;
+            if i > 0 {
+                write!(writer, "{}", delimiter).map_err(|e| e.to_string())?;
+            }
+            if let Some(username) = username {
+                write!(writer, "{}\t", username).map_err(|e| e.to_string())?;
+            }
+            match bulk {
+                true => write!(writer, "{}", secret),
+                false => write!(writer, "{}\t{:.1} bits", secret, bits),
+            }
+            .map_err(|e| e.to_string())?;
+        }
+
+        writer.flush().map_err(|e| e.to_string())
+    }
+}
+
+impl Argument for Generate {
+    fn short(&self) -> char {
+        'g'
+    }
+
+    fn argument(&self) -> String {
+        "generate".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from([
+            "-p",
+            "--password",
+            "-u",
+            "--username",
+            "-c",
+            "--classes",
+            "--custom",
+            "--graphemes",
+            "--nfc",
+            "--layout",
+            "-x",
+            "--exclude",
+            "-w",
+            "--words",
+            "--separator",
+            "--capitalize",
+            "--digit",
+            "--symbol",
+            "-e",
+            "--min-entropy",
+            "-m",
+            "--mode",
+            "--pattern",
+            "--count",
+            "--null",
+            "--rules",
+            "--domain",
+            "--pin",
+            "--format",
+            "--prefix",
+            "--recovery-codes",
+        ])
+        .contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: Generate a password with the given length and username.
+            If no length is given, the default length is 16. 
+            If no username is given, only a password is generated. 
+            If a username is given, a password and a username are generated.
+            The username is either a style, words (default, e.g. brave-otter-4821), random or email,
+            or a fixed name that is returned as-is. Email aliases use the given domain.
+            Classes are a comma separated list of upper, lower, digits, symbols and custom, each one
+            optionally followed by the minimum amount of characters of that class, e.g. upper:2 (default 1).
+            If an amount of words is given, a diceware passphrase is generated instead, with words
+            joined by the separator (default -), optionally capitalized and with a digit or symbol added.
+            The mode is one of password (default), passphrase, pronounceable, pattern or pin. Pronounceable
+            passwords alternate consonant and vowel clusters of the letters the classes allow and end with
+            the digits, symbols and custom characters the classes require.
+            Patterns copy every character as-is except the placeholders A (upper), a (lower), 9 (digits),
+            ! (symbols), * (any) and the hashcat masks ?u ?l ?d ?s ?a ?h ?H; use \\ to escape them.
+            If a minimum entropy in bits is given, the length or amount of words is derived from it.
+            A single secret is returned next to its entropy in bits.
+            Rules in the passwordrules syntax, e.g. \"minlength: 12; required: lower; max-consecutive: 2;\",
+            replace the classes and clamp the length between their minimum and maximum length.
+            If a count is given, that many secrets are streamed, one per line or NUL separated with --null,
+            without their entropy.
+            A PIN is made of the given amount of digits (default 4), never a run, a repeat, a palindrome,
+            a date or one of the most common PINs.
+            A token is made of the given amount of random bytes (default 16) in the given format: hex,
+            base32, base64url or prefixed, an API token like ghp_<base62><CRC32> with the given prefix
+            (default mo) whose checksum is checked by verify. The uuid (v4) and ulid formats have a fixed size.
+            Recovery codes are a batch of the given amount (default 10) of one-time codes like
+            ABCD-EFGH-JKLM, without 0, 1, I, L and O and ending with a check character. Store them as-is
+            in a file and mark them used with redeem.
+            Custom sets may contain any Unicode characters and the length is counted in characters. With
+            --graphemes, every grapheme cluster of the set, e.g. an accented letter written with a combining
+            mark, is drawn as a single character. With --nfc, the sets are normalized to NFC and split into
+            grapheme clusters too, lone combining marks are left out.
+            Layouts are a comma separated list of qwerty, azerty, qwertz, dvorak and mobile. Only the
+            characters typed without AltGr or a dead key and on the same key of every given layout are
+            drawn, only letters on mobile. Without classes, the ones left empty by the layouts are dropped.
+            Passphrases, PINs, tokens and recovery codes have fixed alphabets and refuse layouts.
+            
+            Usage: generate --password Optional<{{}} --username <<{{}}>>> --domain Optional<{{}}>
+                --classes Optional<{{}}>
+                --custom Optional<{{}}> --graphemes --nfc --exclude Optional<{{}}>
+                --layout Optional<{{}}> --min-entropy Optional<{{}}>
+                --mode Optional<{{}}> --pattern Optional<{{}}> --count Optional<{{}}> --null
+                --rules Optional<{{}}>
+            Usage: generate --pin Optional<{{}}> --count Optional<{{}}>
+            Usage: generate --recovery-codes Optional<{{}}>
+            Usage: generate --format <<{{}}>> --prefix Optional<{{}}> --password Optional<{{}}>
+                --min-entropy Optional<{{}}> --count Optional<{{}}> --null
+            Usage: generate --words <<{{}}>> --separator Optional<{{}}> --capitalize --digit --symbol
+                --min-entropy Optional<{{}}>",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<GenerateParams> for Generate {
+    fn parse(&self, input: &Input) -> Result<GenerateParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        let password = u32::decode(get_flag(input, &["-p", "--password"]));
+        let username = decode_username(input)?;
+        let (length, policy) = match get_flag(input, &["--rules"]) {
+            Some(rules) => {
+                let rules = PasswordRules::decode(Some(rules)).map_err(|e| e.cause)?;
+                let exclude = get_flag(input, &["-x", "--exclude"]).unwrap_or_default();
+
+                (
+                    rules.length(password.unwrap_or(16)),
+                    Policy {
+                        exclude: exclude.to_string(),
+                        ..rules.policy
+                    },
+                )
+            }
+            None => (password.unwrap_or(16), decode_policy(input)?),
+        };
+        let mut policy = Policy {
+            graphemes: input.params.contains_key("--graphemes"),
+            nfc: input.params.contains_key("--nfc"),
+            layouts: decode_layouts(input)?,
+            ..policy
+        };
+        if !policy.layouts.is_empty() && get_flag(input, &["-c", "--classes", "--rules"]).is_none()
+        {
+            let kept = policy
+                .rules
+                .iter()
+                .filter(|rule| !policy.symbols_of(&rule.class).is_empty())
+                .cloned()
+                .collect();
+            policy.rules = kept;
+        }
+        let mode = decode_mode(input)?;
+        let length = match get_flag(input, &["--pin"]).filter(|s| !s.is_empty()) {
+            Some(pin) => u32::decode(Some(pin)).map_err(|e| e.cause)?,
+            None if input.params.contains_key("--pin") => pin::MIN_LENGTH,
+            None => length,
+        };
+        let min_entropy = get_flag(input, &["-e", "--min-entropy"])
+            .map(|bits| u32::decode(Some(bits)).map_err(|e| e.cause))
+            .transpose()?;
+        let count = match get_flag(input, &["--recovery-codes"]).or(get_flag(input, &["--count"])) {
+            Some(count) if !count.is_empty() => {
+                match u32::decode(Some(count)).map_err(|e| e.cause)? {
+                    0 => return Err("Please choose a count greater than 0".to_string()),
+                    count => count,
+                }
+            }
+            _ if mode == Mode::Recovery => 10,
+            _ => 1,
+        };
+        let delimiter = match input.params.contains_key("--null") {
+            true => '\0',
+            false => '\n',
+        };
+
+        Ok(GenerateParams {
+            length,
+            username,
+            policy,
+            mode,
+            min_entropy,
+            count,
+            delimiter,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Derive;
+
+impl Execute<DeriveParams> for Derive {
+    fn execute(&self, params: Option<DeriveParams>) -> Result<String, String> {
+        match params {
+            Some(p) => derive(&p).map_err(|e| e.cause),
+            None => Err("No site was provided".to_string()),
+        }
+    }
+}
+
+impl Argument for Derive {
+    fn short(&self) -> char {
+        'd'
+    }
+
+    fn argument(&self) -> String {
+        "derive".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from([
+            "-s",
+            "--site",
+            "-l",
+            "--login",
+            "-n",
+            "--counter",
+            "-p",
+            "--password",
+            "-c",
+            "--classes",
+            "--custom",
+            "-x",
+            "--exclude",
+        ])
+        .contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: Derive the password of a site from a master secret, the site, the login and a counter.
+            The same inputs always give the same password and nothing is stored.
+            The master secret is read from the next line of the standard input so it stays out of the
+            shell history. If no counter is given, the default counter is 1. The length and classes work as in generate.
+            
+            Usage: derive --site <<{{}}>> --login Optional<{{}}> --counter Optional<{{}}>
+                --password Optional<{{}}> --classes Optional<{{}}> --custom Optional<{{}}>
+                --exclude Optional<{{}}>",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<DeriveParams> for Derive {
+    fn parse(&self, input: &Input) -> Result<DeriveParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        let default = DeriveParams::default();
+        let site = String::decode(get_flag(input, &["-s", "--site"])).map_err(|e| e.cause)?;
+        let login = String::decode(get_flag(input, &["-l", "--login"])).unwrap_or_default();
+        let counter = match get_flag(input, &["-n", "--counter"]) {
+            Some(counter) => u32::decode(Some(counter)).map_err(|e| e.cause)?,
+            None => default.counter,
+        };
+        let length = u32::decode(get_flag(input, &["-p", "--password"])).unwrap_or(default.length);
+
+        Ok(DeriveParams {
+            master: read_hidden("master secret")?,
+            site,
+            login,
+            counter,
+            length,
+            policy: decode_policy(input)?,
+            ..default
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Check;
+
+impl Execute<CheckParams> for Check {
+    fn execute(&self, params: Option<CheckParams>) -> Result<String, String> {
+        let params = params.ok_or_else(|| "No password was provided".to_string())?;
+        let estimate = strength(&params.password, &params.user_inputs);
+
+        let mut lines = vec![
+            format!("score: {}/4", estimate.score),
+            match estimate.guesses < 1e15 {
+                true => format!("guesses: {:.0}", estimate.guesses),
+                false => format!("guesses: {:.2e}", estimate.guesses),
+            },
+        ];
+        lines.extend(
+            estimate.crack_times().into_iter().map(|(attack, seconds)| {
+                format!("crack time, {}: {}", attack, display_time(seconds))
+            }),
+        );
+        lines.extend(estimate.sequence.iter().map(|m| format!("pattern: {}", m)));
+        lines.extend(
+            estimate
+                .warning
+                .map(|warning| format!("warning: {}", warning)),
+        );
+        lines.extend(
+            estimate
+                .suggestions
+                .iter()
+                .map(|suggestion| format!("suggestion: {}", suggestion)),
+        );
+
+        Ok(lines.join("\n"))
+    }
+}
+
+impl Argument for Check {
+    fn short(&self) -> char {
+        'c'
+    }
+
+    fn argument(&self) -> String {
+        "check".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from(["-i", "--inputs"]).contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: Estimate the strength of a password, read from the next line of the standard input so it
+            stays out of the shell history. The password is split into the easiest to guess sequence of common
+            passwords, words, l33t and reversed words, keyboard walks, repeats, sequences and dates.
+            The score goes from 0 (too guessable) to 4 (very unguessable) and comes with the estimated guesses,
+            the time to crack it online and offline and suggestions to make it stronger.
+            Inputs are a comma separated list of words related to you or the site, which are guessed first.
+            
+            Usage: check --inputs Optional<{{}}>",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<CheckParams> for Check {
+    fn parse(&self, input: &Input) -> Result<CheckParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        let user_inputs = get_flag(input, &["-i", "--inputs"])
+            .map(|inputs| {
+                inputs
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(CheckParams {
+            password: read_hidden("password")?,
+            user_inputs,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Breach;
+
+impl Execute<BreachParams> for Breach {
+    fn execute(&self, params: Option<BreachParams>) -> Result<String, String> {
+        let params = params.ok_or_else(|| "No breach file was provided".to_string())?;
+
+        match pwned(&params.file, &params.password).map_err(|e| e.cause)? {
+            Some(count) => Ok(format!("pwned: {}", count)),
+            None => Ok("pwned: 0".to_string()),
+        }
+    }
+}
+
+impl Argument for Breach {
+    fn short(&self) -> char {
+        'b'
+    }
+
+    fn argument(&self) -> String {
+        "breach".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from(["-f", "--file"]).contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: Check whether a password appears in a local Pwned Passwords file, the SHA-1 version
+            ordered by hash. The password is read from the next line of the standard input and only its hash
+            is looked up, nothing leaves the machine. Returns how many times it was seen in breaches.
+            
+            Usage: breach --file <<{{}}>>",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<BreachParams> for Breach {
+    fn parse(&self, input: &Input) -> Result<BreachParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        let file = match get_flag(input, &["-f", "--file"]).filter(|s| !s.is_empty()) {
+            Some(file) => PathBuf::from(file),
+            None => return Err("No breach file was provided, use --file <path>".to_string()),
+        };
+
+        Ok(BreachParams {
+            password: read_hidden("password")?,
+            file,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Otp;
+
+impl Execute<OtpParams> for Otp {
+    fn execute(&self, params: Option<OtpParams>) -> Result<String, String> {
+        let params =
+            params.ok_or_else(|| "No one-time password secret was provided".to_string())?;
+        let code = otp(&params.auth, params.time).map_err(|e| e.cause)?;
+
+        match params.auth.kind {
+            OtpKind::Totp { period } => Ok(format!(
+                "{}\t{} seconds",
+                code,
+                remaining(params.time, period)
+            )),
+            OtpKind::Hotp { counter } => Ok(format!("{}\tcounter {}", code, counter)),
+        }
+    }
+}
+
+impl Argument for Otp {
+    fn short(&self) -> char {
+        'o'
+    }
+
+    fn argument(&self) -> String {
+        "otp".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from([
+            "-d",
+            "--digits",
+            "-p",
+            "--period",
+            "-a",
+            "--algorithm",
+            "--counter",
+            "-t",
+            "--time",
+        ])
+        .contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: Print the current one-time password of an otpauth:// URI or a base32 secret, and the
+            seconds before it changes. Without either, the secret is read from the next line of the standard
+            input. The flags override the parameters of the URI: 6 to 8 digits, a period in seconds, the
+            SHA1, SHA256 or SHA512 algorithm and the counter, which makes it a counter based code (HOTP).
+            The time is in seconds since the Unix epoch and defaults to now.
+            
+            Usage: otp Optional<{{}}> --digits Optional<{{}}> --period Optional<{{}}> --algorithm Optional<{{}}>
+            --counter Optional<{{}}> --time Optional<{{}}>",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<OtpParams> for Otp {
+    fn parse(&self, input: &Input) -> Result<OtpParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        let secret = match input.values.first() {
+            Some(secret) => secret.clone(),
+            None => read_hidden("secret")?,
+        };
+        let mut auth = OtpAuth::decode(Some(&secret)).map_err(|e| e.cause)?;
+
+        if let Some(digits) = get_flag(input, &["-d", "--digits"]) {
+            auth.digits = u32::decode(Some(digits)).map_err(|e| e.cause)?;
+        }
+        if let Some(algorithm) = get_flag(input, &["-a", "--algorithm"]) {
+            auth.algorithm = match algorithm.to_ascii_uppercase().replace('-', "").as_str() {
+                "SHA1" => Algorithm::Sha1,
+                "SHA256" => Algorithm::Sha256,
+                "SHA512" => Algorithm::Sha512,
+                _ => {
+                    return Err(format!(
+                        "Unsupported algorithm {}, use SHA1, SHA256 or SHA512",
+                        algorithm
+                    ))
+                }
+            };
+        }
+        if let Some(period) = get_flag(input, &["-p", "--period"]) {
+            auth.kind = OtpKind::Totp {
+                period: u64::decode(Some(period)).map_err(|e| e.cause)?,
+            };
+        }
+        if let Some(counter) = get_flag(input, &["--counter"]) {
+            auth.kind = OtpKind::Hotp {
+                counter: u64::decode(Some(counter)).map_err(|e| e.cause)?,
+            };
+        }
+
+        let time = match get_flag(input, &["-t", "--time"]) {
+            Some(time) => u64::decode(Some(time)).map_err(|e| e.cause)?,
+            None => now()?,
+        };
+
+        Ok(OtpParams { auth, time })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Verify;
+
+impl Execute<String> for Verify {
+    fn execute(&self, params: Option<String>) -> Result<String, String> {
+        let token = params.ok_or_else(|| "No token was provided".to_string())?;
+
+        match verify(&token) {
+            Ok(prefix) => Ok(format!("valid: {}", prefix)),
+            Err(e) => Err(e.cause),
+        }
+    }
+}
+
+impl Argument for Verify {
+    fn short(&self) -> char {
+        'v'
+    }
+
+    fn argument(&self) -> String {
+        "verify".to_string()
+    }
+
+    fn is_valid_flag(_key: &str) -> bool {
+        false
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: Check the CRC32 checksum of a prefixed token made by generate --format prefixed, so
+            mistyped or truncated tokens are caught before any lookup. Without a token, it is read from the
+            next line of the standard input. Returns the prefix of a valid token.
+            
+            Usage: verify Optional<{{}}>",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<String> for Verify {
+    fn parse(&self, input: &Input) -> Result<String, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        match input.values.first() {
+            Some(token) => Ok(token.clone()),
+            None => read_hidden("token"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Redeem;
+
+impl Execute<RedeemParams> for Redeem {
+    fn execute(&self, params: Option<RedeemParams>) -> Result<String, String> {
+        let params = params.ok_or_else(|| "No recovery code was provided".to_string())?;
+        let content = std::fs::read_to_string(&params.file)
+            .map_err(|e| format!("Unable to read {} with error: {}", params.file.display(), e))?;
+
+        let mut set = RecoverySet::decode(Some(&content)).map_err(|e| e.cause)?;
+        let remaining = set.redeem(&params.code).map_err(|e| e.cause)?;
+        replace_private(&params.file, set.encode().as_bytes()).map_err(|e| e.cause)?;
+
+        Ok(format!("remaining: {}", remaining))
+    }
+}
+
+impl Argument for Redeem {
+    fn short(&self) -> char {
+        'e'
+    }
+
+    fn argument(&self) -> String {
+        "redeem".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from(["-f", "--file"]).contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: Mark a recovery code as used in the file where the codes of generate --recovery-codes
+            are stored, one per line. The code is read from the next line of the standard input and may be
+            typed in any case, with or without dashes. Mistyped, unknown and already used codes are
+            rejected. Returns how many codes are left.
+            
+            Usage: redeem --file <<{{}}>>",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<RedeemParams> for Redeem {
+    fn parse(&self, input: &Input) -> Result<RedeemParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        let file = match get_flag(input, &["-f", "--file"]).filter(|s| !s.is_empty()) {
+            Some(file) => PathBuf::from(file),
+            None => {
+                return Err("No recovery codes file was provided, use --file <path>".to_string())
+            }
+        };
+
+        Ok(RedeemParams {
+            code: read_hidden("code")?,
+            file,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Keygen;
+
+impl Execute<KeygenParams> for Keygen {
+    fn execute(&self, params: Option<KeygenParams>) -> Result<String, String> {
+        let params = params.ok_or_else(|| "No output file was provided".to_string())?;
+        let mut rng = default_rng();
+
+        let pair = keypair(
+            params.kind,
+            &params.comment,
+            params.passphrase.as_deref(),
+            &mut rng,
+        )
+        .map_err(|e| e.cause)?;
+        let public = write_keypair(&pair, &params.output).map_err(|e| e.cause)?;
+
+        let mut lines = vec![
+            format!("private: {}", params.output.display()),
+            format!("public: {}", public.display()),
+        ];
+        lines.extend(
+            params
+                .passphrase
+                .map(|passphrase| format!("passphrase: {}", passphrase)),
+        );
+
+        Ok(lines.join("\n"))
+    }
+}
+
+impl Argument for Keygen {
+    fn short(&self) -> char {
+        'k'
+    }
+
+    fn argument(&self) -> String {
+        "keygen".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from([
+            "-t",
+            "--type",
+            "-o",
+            "--output",
+            "--comment",
+            "--passphrase",
+        ])
+        .contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: Generate a keypair and write the private key to the output file and the public key next
+            to it with a .pub extension, both only readable by their owner (0600). Existing files are never
+            overwritten. The type is ed25519 (default), an OpenSSH key with the given comment, x25519, PEM
+            encoded keys, or wireguard, the base64 keys of wg genkey and wg pubkey.
+            With --passphrase, the SSH private key is encrypted with a new diceware passphrase, which is
+            returned once along with the paths.
+            
+            Usage: keygen --output <<{{}}>> --type Optional<{{}}> --comment Optional<{{}}> --passphrase",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<KeygenParams> for Keygen {
+    fn parse(&self, input: &Input) -> Result<KeygenParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        let kind = match get_flag(input, &["-t", "--type"])
+            .filter(|s| !s.is_empty())
+            .unwrap_or("ed25519")
+            .to_lowercase()
+            .as_str()
+        {
+            "ed25519" | "ssh" => KeyType::Ed25519,
+            "x25519" => KeyType::X25519,
+            "wireguard" | "wg" => KeyType::WireGuard,
+            kind => {
+                return Err(format!(
+                    "Unknown key type: {}, use ed25519, x25519 or wireguard",
+                    kind
+                ))
+            }
+        };
+        let output = match get_flag(input, &["-o", "--output"]).filter(|s| !s.is_empty()) {
+            Some(output) => PathBuf::from(output),
+            None => return Err("No output file was provided, use --output <path>".to_string()),
+        };
+        let passphrase = match input.params.contains_key("--passphrase") {
+            true => Some(new_passphrase()?),
+            false => None,
+        };
+
+        Ok(KeygenParams {
+            kind,
+            output,
+            comment: get_flag(input, &["--comment"])
+                .unwrap_or_default()
+                .to_string(),
+            passphrase,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Cert;
+
+impl Execute<CertParams> for Cert {
+    fn execute(&self, params: Option<CertParams>) -> Result<String, String> {
+        let params = params.ok_or_else(|| "No certificate action was provided".to_string())?;
+        let mut rng = default_rng();
+
+        if !params.file.exists() {
+            return Err(format!(
+                "No vault at {}, the keys are kept in it, create it first with init",
+                params.file.display()
+            ));
+        }
+        let _lock = lock(&params.file).map_err(|e| e.cause)?;
+        let keyfile = params
+            .keyfile
+            .as_deref()
+            .map(vault::read_keyfile)
+            .transpose()
+            .map_err(|e| e.cause)?;
+        let mut vault =
+            vault::load(&params.file, &params.master, keyfile.as_deref()).map_err(|e| e.cause)?;
+
+        // The certificate is only kept once its key is saved in the vault.
+        let store = |vault: &mut Vault, certified: &CertifiedKey| {
+            let path = save(&params.store, vault, certified, now()?, &mut default_rng())
+                .map_err(|e| e.cause)?;
+            match vault::save(&params.file, vault, false, &mut default_rng()) {
+                Ok(()) => Ok(path),
+                Err(e) => {
+                    let _ = fs::remove_file(&path);
+                    Err(e.cause)
+                }
+            }
+        };
+
+        let sans = match &params.action {
+            CertAction::Ca => {
+                let ca = create_ca(&params.name, params.days, &mut rng).map_err(|e| e.cause)?;
+                let path = store(&mut vault, &ca)?;
+                return Ok(format!("ca: {}", path.display()));
+            }
+            CertAction::Issue(sans) => sans,
+        };
+
+        let ca = load(&params.store, &vault, &params.ca).map_err(|e| {
+            format!(
+                "{}, create the certificate authority first with cert ca --name {}",
+                e.cause, params.ca
+            )
+        })?;
+        let leaf = issue(&ca, &params.name, sans, params.days, &mut rng).map_err(|e| e.cause)?;
+        let path = store(&mut vault, &leaf)?;
+        let mut lines = vec![format!("cert: {}", path.display())];
+
+        if let Some(output) = &params.output {
+            let with_extension = |extension: &str| {
+                let mut path = output.as_os_str().to_owned();
+                path.push(extension);
+                PathBuf::from(path)
+            };
+
+            match (params.format, &params.passphrase) {
+                (BundleFormat::Pkcs12, Some(passphrase)) => {
+                    let bundle = with_extension(".p12");
+                    let content = pkcs12(&leaf, &ca, passphrase).map_err(|e| e.cause)?;
+                    write_private(&bundle, &content).map_err(|e| e.cause)?;
+                    lines.push(format!("bundle: {}", bundle.display()));
+                    lines.push(format!("passphrase: {}", passphrase));
+                }
+                (BundleFormat::Pkcs12, None) => {
+                    return Err("A PKCS#12 bundle needs a passphrase".to_string())
+                }
+                (BundleFormat::Pem, _) => {
+                    let (chain, key) = (with_extension(".crt"), with_extension(".key"));
+                    write_private(&chain, pem_chain(&leaf, &ca).as_bytes()).map_err(|e| e.cause)?;
+                    write_private(&key, leaf.key.as_bytes()).map_err(|e| e.cause)?;
+                    lines.push(format!("chain: {}", chain.display()));
+                    lines.push(format!("key: {}", key.display()));
+                }
+            }
+        }
+
+        Ok(lines.join("\n"))
+    }
+}
+
+impl Argument for Cert {
+    fn short(&self) -> char {
+        'r'
+    }
+
+    fn argument(&self) -> String {
+        "cert".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from([
+            "-n",
+            "--name",
+            "--ca",
+            "--days",
+            "-f",
+            "--format",
+            "-o",
+            "--output",
+            "--keyfile",
+        ])
+        .contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: Manage a local certificate authority for development TLS. Certificates are kept in the
+            certs directory of the store ($MOKUBA_HOME, ~/.mokuba by default) and their keys in the vault,
+            created with init, as the entry titled cert:<name>. Neither is ever overwritten. The master
+            password of the vault is read from the next line of the standard input.
+            ca creates an ECDSA P-256 certificate authority with the given name (default mokuba), valid for
+            the given days (default 3650). Its certificate is the one to trust in the browser or system.
+            issue signs a certificate for the given DNS names and IP addresses with the given CA, valid for
+            the given days (default 825) and stored under the given name (default the first DNS name).
+            With an output, it is also exported as pem, the chain in <output>.crt and the key in
+            <output>.key, or p12, a PKCS#12 bundle in <output>.p12 encrypted with a new diceware
+            passphrase returned once.
+            
+            Usage: cert ca --name Optional<{{}}> --days Optional<{{}}> --keyfile Optional<{{}}>
+            Usage: cert issue <<{{}}>>... --ca Optional<{{}}> --name Optional<{{}}> --days Optional<{{}}>
+                --format Optional<{{}}> --output Optional<{{}}> --keyfile Optional<{{}}>",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<CertParams> for Cert {
+    fn parse(&self, input: &Input) -> Result<CertParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        let name = get_flag(input, &["-n", "--name"]).filter(|s| !s.is_empty());
+        let ca = get_flag(input, &["--ca"])
+            .filter(|s| !s.is_empty())
+            .unwrap_or("mokuba")
+            .to_string();
+        let days = get_flag(input, &["--days"])
+            .map(|days| u32::decode(Some(days)).map_err(|e| e.cause))
+            .transpose()?;
+
+        let (action, name, days) = match input.values.split_first() {
+            Some((action, _)) if action == "ca" => (
+                CertAction::Ca,
+                name.map_or(ca.clone(), |name| name.to_string()),
+                days.unwrap_or(3650),
+            ),
+            Some((action, sans)) if action == "issue" => match sans.first() {
+                Some(first) => (
+                    CertAction::Issue(sans.to_vec()),
+                    name.map_or(first.replace('*', "wildcard"), |name| name.to_string()),
+                    days.unwrap_or(825),
+                ),
+                None => return Err("No DNS name or IP address was provided to issue".to_string()),
+            },
+            Some((action, _)) => {
+                return Err(format!("Unknown cert action: {}, use ca or issue", action))
+            }
+            None => return Err("No cert action was provided, use ca or issue".to_string()),
+        };
+
+        let format = match get_flag(input, &["-f", "--format"])
+            .filter(|s| !s.is_empty())
+            .unwrap_or("pem")
+            .to_lowercase()
+            .as_str()
+        {
+            "pem" => BundleFormat::Pem,
+            "p12" | "pkcs12" | "pfx" => BundleFormat::Pkcs12,
+            format => return Err(format!("Unknown format: {}, use pem or p12", format)),
+        };
+        let output = get_flag(input, &["-o", "--output"])
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from);
+        let passphrase = match (format, &output) {
+            (BundleFormat::Pkcs12, Some(_)) => Some(new_passphrase()?),
+            _ => None,
+        };
+
+        Ok(CertParams {
+            action,
+            name,
+            ca,
+            days,
+            format,
+            output,
+            passphrase,
+            store: home().join("certs"),
+            master: read_hidden("master password")?,
+            keyfile: keyfile_path(input)?,
+            file: vault_file(),
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Init;
+
+impl Execute<VaultParams> for Init {
+    fn execute(&self, params: Option<VaultParams>) -> Result<String, String> {
+        run_vault(params)
+    }
+}
+
+impl Argument for Init {
+    fn short(&self) -> char {
+        'i'
+    }
+
+    fn argument(&self) -> String {
+        "init".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from(["--keyfile", "--history"]).contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: Create the encrypted vault of the store ($MOKUBA_HOME, ~/.mokuba by default), where add,
+            get, list and rm keep the password entries. The master password is read from the next line of the
+            standard input. The vault key is sealed with a key derived from the master
+            password with Argon2id, and every entry with the vault key using XChaCha20-Poly1305. An existing
+            vault is never overwritten. Every entry keeps the given amount of previous passwords (default 10).
+            With a keyfile, any file whose SHA-256 hash is stretched along with the master password, the
+            vault also needs it to unlock, so every vault command must be given the same keyfile. Keep it
+            apart from the vault, on removable media for instance, see keyfile generate.
+            
+            Usage: init --history Optional<{{}}> --keyfile Optional<{{}}>",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<VaultParams> for Init {
+    fn parse(&self, input: &Input) -> Result<VaultParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        let history = match get_flag(input, &["--history"]) {
+            Some(history) => u32::decode(Some(history)).map_err(|e| e.cause)?,
+            None => DEFAULT_HISTORY,
+        };
+
+        Ok(VaultParams {
+            action: VaultAction::Init(Kdf::default(), history),
+            master: read_hidden("master password")?,
+            keyfile: keyfile_path(input)?,
+            file: vault_file(),
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Add;
+
+impl Execute<VaultParams> for Add {
+    fn execute(&self, params: Option<VaultParams>) -> Result<String, String> {
+        run_vault(params)
+    }
+}
+
+impl Argument for Add {
+    fn short(&self) -> char {
+        'a'
+    }
+
+    fn argument(&self) -> String {
+        "add".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from([
+            "--keyfile",
+            "--generate",
+            "-u",
+            "--username",
+            "--url",
+            "--notes",
+            "--tags",
+            "--update",
+        ])
+        .contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: Add an entry with the given title to the vault. The master password is read first, then
+            the password of the entry, each from the next line of the standard input. With generate, a
+            password of the given length (default 16) is generated instead, get returns it.
+            URLs and tags are comma separated lists. Every entry gets an id, a UUID that get and rm accept
+            in place of the title.
+            With update, the password of the existing entry with the given title or id is replaced instead
+            and the previous one is kept in its history, see history and restore.
+            
+            Usage: add <<{{}}>> --username Optional<{{}}> --url Optional<{{}}> --notes Optional<{{}}>
+                --tags Optional<{{}}> --generate Optional<{{}}> --keyfile Optional<{{}}>
+            Usage: add <<{{}}>> --update --generate Optional<{{}}> --keyfile Optional<{{}}>",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<VaultParams> for Add {
+    fn parse(&self, input: &Input) -> Result<VaultParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        let title = decode_title(input)?;
+        let master = read_hidden("master password")?;
+        let password = match get_flag(input, &["--generate"]) {
+            Some(length) => {
+                let length = match length.is_empty() {
+                    true => 16,
+                    false => u32::decode(Some(length)).map_err(|e| e.cause)?,
+                };
+                gen_with_rng(GenerateParams::new(length, None), &mut default_rng())
+                    .map_err(|e| e.cause)?
+            }
+            None => read_hidden("password")?,
+        };
+        if input.params.contains_key("--update") {
+            return Ok(VaultParams {
+                action: VaultAction::Update(title, password),
+                master,
+                keyfile: keyfile_path(input)?,
+                file: vault_file(),
+            });
+        }
+
+        let list = |keys: &[&str]| {
+            get_flag(input, keys)
+                .unwrap_or_default()
+                .split(',')
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .collect::<Vec<String>>()
+        };
+        let entry = Entry {
+            username: get_flag(input, &["-u", "--username"])
+                .unwrap_or_default()
+                .to_string(),
+            urls: list(&["--url"]),
+            notes: get_flag(input, &["--notes"])
+                .unwrap_or_default()
+                .to_string(),
+            tags: list(&["--tags"]),
+            ..Entry::new(title, password, now()?, &mut default_rng())
+        };
+
+        Ok(VaultParams {
+            action: VaultAction::Add(entry),
+            master,
+            keyfile: keyfile_path(input)?,
+            file: vault_file(),
+        })
+    }
+}
 
-use super::{cmd::Input, codec::Codec, error::GetInputError};
-use crate::gen;
+#[derive(Debug, Copy, Clone)]
+pub struct Get;
 
-use super::{
-    cmd::{Argument, Execute, Parse, HELP},
-    param::GenerateParams,
-};
+impl Execute<VaultParams> for Get {
+    fn execute(&self, params: Option<VaultParams>) -> Result<String, String> {
+        run_vault(params)
+    }
+}
+
+impl Argument for Get {
+    fn short(&self) -> char {
+        's'
+    }
+
+    fn argument(&self) -> String {
+        "get".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from(["--keyfile", "--entry"]).contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: Return the password of the vault entry with the given title, ignoring case, or id. The
+            master password is read from the next line of the standard input. With entry, every
+            field is returned instead, one <field>\t<value> line each, with tabs and line breaks escaped.
+            
+            Usage: get <<{{}}>> --entry --keyfile Optional<{{}}>",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<VaultParams> for Get {
+    fn parse(&self, input: &Input) -> Result<VaultParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        let title = decode_title(input)?;
+        let action = match input.params.contains_key("--entry") {
+            true => VaultAction::Show(title),
+            false => VaultAction::Get(title),
+        };
+
+        Ok(VaultParams {
+            action,
+            master: read_hidden("master password")?,
+            keyfile: keyfile_path(input)?,
+            file: vault_file(),
+        })
+    }
+}
 
-// Available commands
 #[derive(Debug, Copy, Clone)]
-pub struct Generate;
+pub struct List;
 
-impl Execute<GenerateParams> for Generate {
-    fn execute(&self, params: Option<GenerateParams>) -> Result<String, String> {
-        match params {
-            Some(p) => gen(p).map_err(|e| e.cause),
-            None => gen(GenerateParams::new(16, None)).map_err(|e| e.cause),
+impl Execute<VaultParams> for List {
+    fn execute(&self, params: Option<VaultParams>) -> Result<String, String> {
+        run_vault(params)
+    }
+}
+
+impl Argument for List {
+    fn short(&self) -> char {
+        'l'
+    }
+
+    fn argument(&self) -> String {
+        "list".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from(["--keyfile"]).contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: List the titles of the vault entries, one per line. The master password is read from the
+            next line of the standard input.
+            
+            Usage: list --keyfile Optional<{{}}>",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<VaultParams> for List {
+    fn parse(&self, input: &Input) -> Result<VaultParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
         }
+
+        Ok(VaultParams {
+            action: VaultAction::List,
+            master: read_hidden("master password")?,
+            keyfile: keyfile_path(input)?,
+            file: vault_file(),
+        })
     }
 }
 
-impl Argument for Generate {
+#[derive(Debug, Copy, Clone)]
+pub struct Remove;
+
+impl Execute<VaultParams> for Remove {
+    fn execute(&self, params: Option<VaultParams>) -> Result<String, String> {
+        run_vault(params)
+    }
+}
+
+impl Argument for Remove {
     fn short(&self) -> char {
-        'g'
+        'x'
     }
 
     fn argument(&self) -> String {
-        "generate".to_string()
+        "rm".to_string()
     }
 
     fn is_valid_flag(key: &str) -> bool {
-        HashSet::from(["-p", "--password", "-u", "--username"]).contains(key)
+        HashSet::from(["--keyfile"]).contains(key)
     }
 
     fn description(&self) -> String {
         format!(
-            "{} {}: Generate a password with the given length and username. 
-            If no length is given, the default length is 16. 
-            If no username is given, only a password is generated. 
-            If a username is given, a password and a username are generated.
+            "{} {}: Remove the vault entry with the given title, ignoring case, or id. The master password is
+            read from the next line of the standard input.
             
-            Usage: generate --password Optional<{{}} --username <<{{}}>>>",
+            Usage: rm <<{{}}>> --keyfile Optional<{{}}>",
             self.short(),
             self.argument()
         )
     }
 }
 
-impl Parse<GenerateParams> for Generate {
-    fn parse(&self, input: &Input) -> Result<GenerateParams, String> {
+impl Parse<VaultParams> for Remove {
+    fn parse(&self, input: &Input) -> Result<VaultParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        Ok(VaultParams {
+            action: VaultAction::Remove(decode_title(input)?),
+            master: read_hidden("master password")?,
+            keyfile: keyfile_path(input)?,
+            file: vault_file(),
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct History;
+
+impl Execute<VaultParams> for History {
+    fn execute(&self, params: Option<VaultParams>) -> Result<String, String> {
+        run_vault(params)
+    }
+}
+
+impl Argument for History {
+    fn short(&self) -> char {
+        'y'
+    }
+
+    fn argument(&self) -> String {
+        "history".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from(["--keyfile"]).contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: List the previous passwords of the vault entry with the given title or id, the most recent
+            first, as their version and when they were replaced. The amount kept is set by init --history.
+            The master password is read from the next line of the standard input.
+            
+            Usage: history <<{{}}>> --keyfile Optional<{{}}>",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<VaultParams> for History {
+    fn parse(&self, input: &Input) -> Result<VaultParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        Ok(VaultParams {
+            action: VaultAction::History(decode_title(input)?),
+            master: read_hidden("master password")?,
+            keyfile: keyfile_path(input)?,
+            file: vault_file(),
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Restore;
+
+impl Execute<VaultParams> for Restore {
+    fn execute(&self, params: Option<VaultParams>) -> Result<String, String> {
+        run_vault(params)
+    }
+}
+
+impl Argument for Restore {
+    fn short(&self) -> char {
+        'u'
+    }
+
+    fn argument(&self) -> String {
+        "restore".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from(["-n", "--version", "--keyfile"]).contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: Put back a previous password of the vault entry with the given title or id, by its version
+            in history (default 1, the previous one). The replaced password is kept in the history, so a
+            restore can be undone. The master password is read from the next line of the standard input.
+            
+            Usage: restore <<{{}}>> --version Optional<{{}}> --keyfile Optional<{{}}>",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<VaultParams> for Restore {
+    fn parse(&self, input: &Input) -> Result<VaultParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        let title = decode_title(input)?;
+        let version = match get_flag(input, &["-n", "--version"]) {
+            Some(version) => u32::decode(Some(version)).map_err(|e| e.cause)? as usize,
+            None => 1,
+        };
+
+        Ok(VaultParams {
+            action: VaultAction::Restore(title, version),
+            master: read_hidden("master password")?,
+            keyfile: keyfile_path(input)?,
+            file: vault_file(),
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Passwd;
+
+impl Execute<VaultParams> for Passwd {
+    fn execute(&self, params: Option<VaultParams>) -> Result<String, String> {
+        run_vault(params)
+    }
+}
+
+impl Argument for Passwd {
+    fn short(&self) -> char {
+        'w'
+    }
+
+    fn argument(&self) -> String {
+        "passwd".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from([
+            "--keyfile",
+            "--memory",
+            "--iterations",
+            "--parallelism",
+            "--rotate-key",
+        ])
+        .contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: Change the master password of the vault. The current master password is read first, then
+            the new one, each from the next line of the standard input. The Argon2id cost of the master key
+            is kept unless a memory in KiB, an amount of iterations or a degree of parallelism is given,
+            each of them can only be raised.
+            With rotate-key, the vault key is replaced too and every entry is sealed with the new one, so
+            a copy of the vault key or of an old vault file opens nothing that is saved afterwards. The
+            vault is never decrypted to a file, the new one replaces it atomically. The keyfile, if any,
+            is kept.
+            
+            Usage: passwd --memory Optional<{{}}> --iterations Optional<{{}}> --parallelism Optional<{{}}>
+                --rotate-key --keyfile Optional<{{}}>",
+            self.short(),
+            self.argument()
+        )
+    }
+}
+
+impl Parse<VaultParams> for Passwd {
+    fn parse(&self, input: &Input) -> Result<VaultParams, String> {
+        match validate(self, input) {
+            Ok(_) => (),
+            Err(e) => return Err(e.cause),
+        }
+
+        let cost = |key: &str| {
+            get_flag(input, &[key])
+                .map(|cost| u32::decode(Some(cost)).map_err(|e| e.cause))
+                .transpose()
+        };
+        let (memory, iterations, parallelism) = (
+            cost("--memory")?,
+            cost("--iterations")?,
+            cost("--parallelism")?,
+        );
+        let master = read_hidden("master password")?;
+
+        Ok(VaultParams {
+            action: VaultAction::Passwd {
+                master: read_hidden("new master password")?,
+                memory,
+                iterations,
+                parallelism,
+                rotate: input.params.contains_key("--rotate-key"),
+            },
+            master,
+            keyfile: keyfile_path(input)?,
+            file: vault_file(),
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Keyfile;
+
+impl Execute<KeyfileParams> for Keyfile {
+    fn execute(&self, params: Option<KeyfileParams>) -> Result<String, String> {
+        let params = params.ok_or_else(|| "No keyfile parameters were provided".to_string())?;
+
+        vault::generate_keyfile(&params.output, &mut default_rng()).map_err(|e| e.cause)?;
+        Ok(format!("keyfile: {}", params.output.display()))
+    }
+}
+
+impl Argument for Keyfile {
+    fn short(&self) -> char {
+        'f'
+    }
+
+    fn argument(&self) -> String {
+        "keyfile".to_string()
+    }
+
+    fn is_valid_flag(key: &str) -> bool {
+        HashSet::from(["-o", "--output"]).contains(key)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} {}: Generate a keyfile of {} random bytes for init --keyfile, drawn from the same generator as
+            generate, and write it to the output file, only readable by its owner (0600). Existing files are
+            never overwritten. Without the keyfile the vault cannot be unlocked, keep a copy of it.
+            
+            Usage: keyfile generate --output <<{{}}>>",
+            self.short(),
+            self.argument(),
+            KEYFILE_LENGTH
+        )
+    }
+}
+
+impl Parse<KeyfileParams> for Keyfile {
+    fn parse(&self, input: &Input) -> Result<KeyfileParams, String> {
         match validate(self, input) {
             Ok(_) => (),
             Err(e) => return Err(e.cause),
         }
 
-        let option_password = input
-            .params
-            .get("-p")
-            .or_else(|| input.params.get("--password"));
+        match input.values.first().map(|action| action.as_str()) {
+            Some("generate") => (),
+            Some(action) => {
+                return Err(format!("Unknown keyfile action: {}, use generate", action))
+            }
+            None => return Err("No keyfile action was provided, use generate".to_string()),
+        }
+        let output = match get_flag(input, &["-o", "--output"]).filter(|s| !s.is_empty()) {
+            Some(output) => PathBuf::from(output),
+            None => return Err("No output file was provided, use --output <path>".to_string()),
+        };
 
-        let option_username = input
-            .params
-            .get("-u")
-            .or_else(|| input.params.get("--username"));
+        Ok(KeyfileParams { output })
+    }
+}
 
-        let password = u32::decode(option_password.map(|s| s.as_str()));
-        let username = String::decode(option_username.map(|s| s.as_str()));
+// Runs the vault commands, which all unlock the vault but init, and save it when they change it. They hold
+// the lock of the vault from the load to the save, init never overwrites a vault.
+fn run_vault(params: Option<VaultParams>) -> Result<String, String> {
+    let params = params.ok_or_else(|| "No vault command was provided".to_string())?;
+    let _lock = match params.action {
+        VaultAction::Init(..) => None,
+        _ if params.file.exists() => Some(lock(&params.file).map_err(|e| e.cause)?),
+        _ => None,
+    };
+    let keyfile = params
+        .keyfile
+        .as_deref()
+        .map(vault::read_keyfile)
+        .transpose()
+        .map_err(|e| e.cause)?;
+    let load =
+        || vault::load(&params.file, &params.master, keyfile.as_deref()).map_err(|e| e.cause);
+    let save = |vault: &Vault, create: bool| {
+        vault::save(&params.file, vault, create, &mut default_rng()).map_err(|e| e.cause)
+    };
 
-        match (password, username) {
-            (Ok(p), Ok(u)) => Ok(GenerateParams::new(p, Some(u))),
-            (Err(_), _) => Ok(GenerateParams::new(16, None)),
-            (_, Err(e)) => Err(e.cause),
+    match params.action.clone() {
+        VaultAction::Init(kdf, history) => {
+            if params.file.exists() {
+                return Err(format!(
+                    "A vault already exists at {}",
+                    params.file.display()
+                ));
+            }
+            if let Some(dir) = params.file.parent() {
+                create_dir(dir).map_err(|e| e.cause)?;
+            }
+            let mut vault =
+                Vault::create(&params.master, keyfile.as_deref(), kdf, &mut default_rng())
+                    .map_err(|e| e.cause)?;
+            vault.history = history;
+            save(&vault, true)?;
+            Ok(format!("created: {}", params.file.display()))
+        }
+        VaultAction::Add(entry) => {
+            let mut vault = load()?;
+            let title = entry.title.clone();
+            vault.add(entry).map_err(|e| e.cause)?;
+            save(&vault, false)?;
+            Ok(format!("added: {}", title))
+        }
+        VaultAction::Update(title, password) => {
+            let mut vault = load()?;
+            let title = vault
+                .set_password(&title, password, now()?)
+                .map_err(|e| e.cause)?
+                .title
+                .clone();
+            save(&vault, false)?;
+            Ok(format!("updated: {}", title))
+        }
+        VaultAction::History(title) => Ok(load()?
+            .get(&title)
+            .map_err(|e| e.cause)?
+            .history
+            .iter()
+            .enumerate()
+            .map(|(i, revision)| format!("{}\treplaced {}", i + 1, display_date(revision.replaced)))
+            .collect::<Vec<String>>()
+            .join("\n")),
+        VaultAction::Restore(title, version) => {
+            let mut vault = load()?;
+            let title = vault
+                .restore(&title, version, now()?)
+                .map_err(|e| e.cause)?
+                .title
+                .clone();
+            save(&vault, false)?;
+            Ok(format!("restored: {} version {}", title, version))
         }
+        VaultAction::Passwd {
+            master,
+            memory,
+            iterations,
+            parallelism,
+            rotate,
+        } => {
+            let mut vault = load()?;
+            let current = vault.kdf();
+            let kdf = Kdf::new(
+                memory.unwrap_or(current.memory),
+                iterations.unwrap_or(current.iterations),
+                parallelism.unwrap_or(current.parallelism),
+            );
+            vault
+                .rekey(&master, keyfile.as_deref(), kdf, rotate, &mut default_rng())
+                .map_err(|e| e.cause)?;
+            save(&vault, false)?;
+            Ok(format!(
+                "changed: {}\nkdf: argon2id m={} t={} p={}\nkey: {}",
+                params.file.display(),
+                kdf.memory,
+                kdf.iterations,
+                kdf.parallelism,
+                if rotate { "rotated" } else { "kept" }
+            ))
+        }
+        VaultAction::Get(title) => Ok(load()?.get(&title).map_err(|e| e.cause)?.password.clone()),
+        VaultAction::Show(title) => Ok(load()?
+            .get(&title)
+            .map_err(|e| e.cause)?
+            .encode()
+            .trim_end()
+            .to_string()),
+        VaultAction::List => Ok(load()?.titles().join("\n")),
+        VaultAction::Remove(title) => {
+            let mut vault = load()?;
+            let entry = vault.remove(&title).map_err(|e| e.cause)?;
+            save(&vault, false)?;
+            Ok(format!("removed: {}", entry.title))
+        }
+    }
+}
+
+// Seconds since the Unix epoch.
+fn now() -> Result<u64, String> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .map_err(|e| format!("Unable to read the clock: {}", e))
+}
+
+// Seconds since the Unix epoch as an RFC 3339 date, in UTC.
+fn display_date(seconds: u64) -> String {
+    OffsetDateTime::from_unix_timestamp(seconds as i64)
+        .ok()
+        .and_then(|date| date.format(&Rfc3339).ok())
+        .unwrap_or_else(|| seconds.to_string())
+}
+
+// The vault of the store.
+fn vault_file() -> PathBuf {
+    home().join("vault")
+}
+
+// The path of the keyfile given along with the master password, if any.
+fn keyfile_path(input: &Input) -> Result<Option<PathBuf>, String> {
+    match get_flag(input, &["--keyfile"]) {
+        Some("") => Err("No keyfile was provided, use --keyfile <path>".to_string()),
+        keyfile => Ok(keyfile.map(PathBuf::from)),
+    }
+}
+
+// The title of a vault entry is made of the values before the first flag.
+fn decode_title(input: &Input) -> Result<String, String> {
+    match input.values.join(" ") {
+        title if title.trim().is_empty() => Err("No entry title was provided".to_string()),
+        title => Ok(title),
     }
 }
 
@@ -143,3 +1845,174 @@ where
 
     Ok(())
 }
+
+// Reads the next line of stdin, which must not be empty.
+fn read_hidden(name: &str) -> Result<String, String> {
+    let secret = read_line_from(std::io::stdin().lock())
+        .map_err(|e| e.cause)?
+        .trim_end_matches(&['\r', '\n'][..])
+        .to_string();
+
+    match secret.is_empty() {
+        true => Err(format!("No secret was provided for {}", name)),
+        false => Ok(secret),
+    }
+}
+
+// A diceware passphrase for the files that are encrypted, returned once to the user.
+fn new_passphrase() -> Result<String, String> {
+    let params = GenerateParams {
+        mode: Mode::Passphrase(Passphrase::default()),
+        ..Default::default()
+    };
+
+    gen_with_rng(params, &mut default_rng()).map_err(|e| e.cause)
+}
+
+// Returns the value of the first flag in `keys` that is present in the input.
+fn get_flag<'a>(input: &'a Input, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .find_map(|key| input.params.get(*key))
+        .map(|s| s.as_str())
+}
+
+fn decode_policy(input: &Input) -> Result<Policy, String> {
+    let custom = get_flag(input, &["--custom"]).filter(|s| !s.is_empty());
+
+    let mut rules = match get_flag(input, &["-c", "--classes"]) {
+        Some(classes) => classes
+            .split(',')
+            .map(|class| ClassRule::decode(Some(class)).map_err(|e| e.cause))
+            .collect::<Result<Vec<ClassRule>, String>>()?,
+        None if custom.is_some() => vec![],
+        None => Policy::default().rules,
+    };
+
+    let is_custom = |rule: &ClassRule| matches!(rule.class, CharClass::Custom(_));
+
+    match custom {
+        Some(set) => {
+            if !rules.iter().any(is_custom) {
+                rules.push(ClassRule::new(CharClass::Custom(String::new()), 1));
+            }
+            rules
+                .iter_mut()
+                .filter(|rule| is_custom(rule))
+                .for_each(|rule| rule.class = CharClass::Custom(set.to_string()));
+        }
+        None if rules.iter().any(is_custom) => {
+            return Err(
+                "The custom class needs a set of characters, use --custom <chars>".to_string(),
+            )
+        }
+        None => (),
+    }
+
+    let exclude = get_flag(input, &["-x", "--exclude"]).unwrap_or_default();
+
+    Ok(Policy::new(rules, exclude.to_string()))
+}
+
+fn decode_layouts(input: &Input) -> Result<Vec<Layout>, String> {
+    match get_flag(input, &["--layout"]).filter(|s| !s.is_empty()) {
+        Some(layouts) => layouts
+            .split(',')
+            .map(|layout| Layout::decode(Some(layout)).map_err(|e| e.cause))
+            .collect(),
+        None => Ok(vec![]),
+    }
+}
+
+fn decode_mode(input: &Input) -> Result<Mode, String> {
+    let words = get_flag(input, &["-w", "--words"]);
+    let pattern = get_flag(input, &["--pattern"]);
+    let pin = get_flag(input, &["--pin"]);
+    let format = get_flag(input, &["--format"]);
+    let prefix = get_flag(input, &["--prefix"]);
+    let name = get_flag(input, &["-m", "--mode"])
+        .filter(|s| !s.is_empty())
+        .or(get_flag(input, &["--recovery-codes"]).map(|_| "recovery"))
+        .unwrap_or(match (words, pattern, pin, format.or(prefix)) {
+            (_, _, _, Some(_)) => "token",
+            (_, _, Some(_), _) => "pin",
+            (_, Some(_), _, _) => "pattern",
+            (Some(_), _, _, _) => "passphrase",
+            (None, None, None, None) => "password",
+        });
+
+    match name.to_lowercase().as_str() {
+        "password" => Ok(Mode::Password),
+        "passphrase" => {
+            let default = Passphrase::default();
+
+            Ok(Mode::Passphrase(Passphrase {
+                words: match words {
+                    Some(words) => u32::decode(Some(words)).map_err(|e| e.cause)?,
+                    None => default.words,
+                },
+                separator: get_flag(input, &["--separator"])
+                    .filter(|s| !s.is_empty())
+                    .map_or(default.separator, |s| s.to_string()),
+                capitalize: input.params.contains_key("--capitalize"),
+                digit: input.params.contains_key("--digit"),
+                symbol: input.params.contains_key("--symbol"),
+            }))
+        }
+        "pronounceable" => Ok(Mode::Pronounceable),
+        "pin" => Ok(Mode::Pin),
+        "recovery" => Ok(Mode::Recovery),
+        "pattern" => match pattern.filter(|s| !s.is_empty()) {
+            Some(pattern) => Ok(Mode::Pattern(pattern.to_string())),
+            None => Err("The pattern mode needs a pattern, use --pattern <pattern>".to_string()),
+        },
+        "token" => {
+            let format = format
+                .filter(|s| !s.is_empty())
+                .unwrap_or(if prefix.is_some() { "prefixed" } else { "hex" });
+
+            match format.to_lowercase().as_str() {
+                "hex" => Ok(Mode::Token(TokenFormat::Hex)),
+                "base32" => Ok(Mode::Token(TokenFormat::Base32)),
+                "base64url" => Ok(Mode::Token(TokenFormat::Base64Url)),
+                "uuid" => Ok(Mode::Token(TokenFormat::Uuid)),
+                "ulid" => Ok(Mode::Token(TokenFormat::Ulid)),
+                "prefixed" => match prefix.filter(|s| !s.is_empty()).unwrap_or("mo") {
+                    prefix
+                        if prefix
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+                    {
+                        Ok(Mode::Token(TokenFormat::Prefixed(prefix.to_string())))
+                    }
+                    prefix => Err(format!(
+                        "Invalid prefix {}, use letters, digits and underscores",
+                        prefix
+                    )),
+                },
+                _ => Err(format!(
+                    "Unknown format: {}, use hex, base32, base64url, uuid, ulid or prefixed",
+                    format
+                )),
+            }
+        }
+        _ => Err(format!("Unknown mode: {}", name)),
+    }
+}
+
+fn decode_username(input: &Input) -> Result<Option<Username>, String> {
+    let domain = get_flag(input, &["--domain"]).filter(|s| !s.is_empty());
+
+    match (get_flag(input, &["-u", "--username"]), domain) {
+        (None, None) => Ok(None),
+        (None, Some(domain)) => Ok(Some(Username::Email(domain.to_string()))),
+        (Some(style), domain) => match (style.to_lowercase().as_str(), domain) {
+            ("" | "words", _) => Ok(Some(Username::Words)),
+            ("random", _) => Ok(Some(Username::Random)),
+            ("email", Some(domain)) => Ok(Some(Username::Email(domain.to_string()))),
+            ("email", None) => {
+                Err("Email aliases need a domain, use --domain <domain>".to_string())
+            }
+            _ => Ok(Some(Username::Fixed(style.to_string()))),
+        },
+    }
+}
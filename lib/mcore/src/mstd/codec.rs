@@ -17,7 +17,7 @@ pub trait Codec<A> {
 
     let str = "hello world".to_string();
     assert_eq!(str.encode(), "hello world");
-    assert_eq!(Some("hello world".to_string()).unwrap(), str);
+    assert_eq!(String::decode(Some("hello world")).unwrap(), str);
     assert!(String::is(str));
     ```
     */
@@ -286,7 +286,7 @@ mod tests {
     fn test_string_should_succeed() {
         let str = "hello world".to_string();
         assert_eq!(str.encode(), "hello world");
-        assert_eq!(Some("hello world".to_string()).unwrap(), str);
+        assert_eq!(String::decode(Some("hello world")).unwrap(), str);
         assert!(String::is(str));
     }
 
@@ -0,0 +1,289 @@
+use super::error::{DecodeError, ErrorCode::UnableToDecodeT};
+
+// RFC 4648 base32 alphabet.
+static BASE32: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+// RFC 4648 base64 alphabet.
+static BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+// RFC 4648 base64 alphabet, safe for URLs and file names.
+static BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+// Crockford's base32 alphabet, without I, L, O and U.
+static CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+pub static BASE62: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Encodes `bytes` in lowercase hexadecimal.
+pub fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/**
+Encodes `bytes` in RFC 4648 base32, without padding.
+
+# Examples
+
+```
+use mcore::mstd::encoding::base32_encode;
+
+assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+```
+*/
+pub fn base32_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let (mut buffer, mut bits) = (0u32, 0);
+
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32[(buffer >> bits) as usize & 31] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(BASE32[(buffer << (5 - bits)) as usize & 31] as char);
+    }
+
+    encoded
+}
+
+/**
+Decodes RFC 4648 base32, ignoring case, padding, spaces and dashes, as secrets are often written in groups.
+
+# Examples
+
+```
+use mcore::mstd::encoding::base32_decode;
+
+assert_eq!(base32_decode("mzxw 6ytb oi======").unwrap(), b"foobar");
+assert!(base32_decode("MZXW1").is_err());
+```
+*/
+pub fn base32_decode(s: &str) -> Result<Vec<u8>, DecodeError> {
+    let mut bytes = Vec::with_capacity(s.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0);
+
+    for c in s.chars().filter(|c| !matches!(c, ' ' | '-' | '=')) {
+        let value = BASE32
+            .iter()
+            .position(|b| *b as char == c.to_ascii_uppercase())
+            .ok_or_else(|| {
+                DecodeError::new(
+                    UnableToDecodeT,
+                    format!("Unable to decode {} from base32, {:?} is not allowed", s, c),
+                )
+            })?;
+
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+
+    Ok(bytes)
+}
+
+/**
+Encodes `bytes` in RFC 4648 base64url, without padding.
+
+# Examples
+
+```
+use mcore::mstd::encoding::base64url_encode;
+
+assert_eq!(base64url_encode(b"foobar"), "Zm9vYmFy");
+assert_eq!(base64url_encode(&[0xfb, 0xff]), "-_8");
+```
+*/
+pub fn base64url_encode(bytes: &[u8]) -> String {
+    base64(bytes, BASE64URL)
+}
+
+/**
+Encodes `bytes` in RFC 4648 base64, padded with `=`, as in PEM files and WireGuard keys.
+
+# Examples
+
+```
+use mcore::mstd::encoding::base64_encode;
+
+assert_eq!(base64_encode(b"fooba"), "Zm9vYmE=");
+assert_eq!(base64_encode(&[0xfb, 0xff]), "+/8=");
+```
+*/
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = base64(bytes, BASE64);
+    while !encoded.len().is_multiple_of(4) {
+        encoded.push('=');
+    }
+
+    encoded
+}
+
+/**
+Decodes RFC 4648 base64, with or without padding.
+
+# Examples
+
+```
+use mcore::mstd::encoding::base64_decode;
+
+assert_eq!(base64_decode("Zm9vYmE=").unwrap(), b"fooba");
+assert!(base64_decode("Zm9v-_8").is_err());
+```
+*/
+pub fn base64_decode(s: &str) -> Result<Vec<u8>, DecodeError> {
+    let mut bytes = Vec::with_capacity(s.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0);
+
+    for c in s.trim_end_matches('=').chars() {
+        let value = BASE64.iter().position(|b| *b as char == c).ok_or_else(|| {
+            DecodeError::new(
+                UnableToDecodeT,
+                format!("Unable to decode {} from base64, {:?} is not allowed", s, c),
+            )
+        })?;
+
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+
+    Ok(bytes)
+}
+
+fn base64(bytes: &[u8], alphabet: &[u8; 64]) -> String {
+    let mut encoded = String::with_capacity((bytes.len() * 4).div_ceil(3));
+    let (mut buffer, mut bits) = (0u32, 0);
+
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 6 {
+            bits -= 6;
+            encoded.push(alphabet[(buffer >> bits) as usize & 63] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(alphabet[(buffer << (6 - bits)) as usize & 63] as char);
+    }
+
+    encoded
+}
+
+/// Encodes the 128 bits of `value` in Crockford's base32, as the 26 characters of a ULID.
+pub fn crockford_encode(value: u128) -> String {
+    (0..26)
+        .rev()
+        .map(|i| CROCKFORD[(value >> (5 * i)) as usize & 31] as char)
+        .collect()
+}
+
+/**
+Encodes `bytes`, read as a big-endian number, in base62 with exactly `width` characters, padded with `0`.
+The width has to be large enough for the number, `ceil(8 * bytes.len() / log2(62))` always is.
+
+# Examples
+
+```
+use mcore::mstd::encoding::base62_encode;
+
+assert_eq!(base62_encode(&[0, 62], 3), "010");
+```
+*/
+pub fn base62_encode(bytes: &[u8], width: usize) -> String {
+    let mut number = bytes.to_vec();
+    let mut digits = Vec::with_capacity(width);
+
+    // Long division of the number by 62, collecting the remainders as the digits from the last one.
+    while digits.len() < width {
+        let mut remainder = 0u32;
+        for byte in number.iter_mut() {
+            let value = (remainder << 8) | *byte as u32;
+            *byte = (value / 62) as u8;
+            remainder = value % 62;
+        }
+        digits.push(BASE62[remainder as usize] as char);
+    }
+
+    digits.into_iter().rev().collect()
+}
+
+/**
+CRC-32 (ISO-HDLC, as used by zlib and PNG) of `bytes`.
+
+# Examples
+
+```
+use mcore::mstd::encoding::crc32;
+
+assert_eq!(crc32(b"123456789"), 0xCBF43926);
+```
+*/
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(u32::MAX, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| match crc & 1 {
+            1 => (crc >> 1) ^ 0xEDB88320,
+            _ => crc >> 1,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base32_round_trip() {
+        // RFC 4648 test vectors
+        let vectors = [
+            ("", ""),
+            ("f", "MY"),
+            ("fo", "MZXQ"),
+            ("foo", "MZXW6"),
+            ("foob", "MZXW6YQ"),
+            ("fooba", "MZXW6YTB"),
+            ("foobar", "MZXW6YTBOI"),
+        ];
+
+        for (plain, encoded) in vectors {
+            assert_eq!(base32_encode(plain.as_bytes()), encoded);
+            assert_eq!(base32_decode(encoded).unwrap(), plain.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_base64url_and_hex_encode() {
+        // RFC 4648 test vectors
+        let vectors = [
+            ("", ""),
+            ("f", "Zg"),
+            ("fo", "Zm8"),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg"),
+            ("fooba", "Zm9vYmE"),
+            ("foobar", "Zm9vYmFy"),
+        ];
+
+        for (plain, encoded) in vectors {
+            assert_eq!(base64url_encode(plain.as_bytes()), encoded);
+            assert_eq!(
+                base64_encode(plain.as_bytes()),
+                format!("{}{}", encoded, "=".repeat((4 - encoded.len() % 4) % 4))
+            );
+            assert_eq!(base64_decode(encoded).unwrap(), plain.as_bytes());
+        }
+        assert_eq!(hex_encode(&[0x00, 0xab, 0xff]), "00abff");
+    }
+
+    #[test]
+    fn test_base62_encode_pads_to_the_width() {
+        assert_eq!(base62_encode(&[], 2), "00");
+        assert_eq!(base62_encode(&[255, 255, 255, 255], 6), "4gfFC3");
+        assert_eq!(base62_encode(&[255; 22], 30).len(), 30);
+        assert_eq!(crockford_encode(u128::MAX), "7ZZZZZZZZZZZZZZZZZZZZZZZZZ");
+    }
+}
@@ -11,6 +11,17 @@ pub enum ErrorCode {
     UnableToEncodeT,
     UnableToDecodeT,
     UnableToParseInputToT,
+    PolicyCannotBeSatisfied,
+    InvalidPattern,
+    UnableToDeriveKey,
+    UnableToReadFile,
+    InvalidSecret,
+    InvalidChecksum,
+    UnableToGenerateKey,
+    InvalidRecoveryCode,
+    UnableToDecrypt,
+    EntryNotFound,
+    EntryAlreadyExists,
 }
 
 // GenError is a custom error type for the password generator.
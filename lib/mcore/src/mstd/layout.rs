@@ -0,0 +1,192 @@
+use super::{
+    codec::Codec,
+    error::{DecodeError, ErrorCode::UnableToDecodeT},
+};
+
+/// A keyboard a password has to be typed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    Qwerty,
+    Azerty,
+    Qwertz,
+    Dvorak,
+    Mobile,
+}
+
+// The base and shift layers of the four rows of an ISO keyboard, from the number row down. The bottom
+// row starts with the key left of Z, missing on US keyboards. Spaces stand for dead keys and empty
+// slots, AltGr is left out.
+static QWERTY: [(&str, &str); 4] = [
+    ("`1234567890-=", "~!@#$%^&*()_+"),
+    ("qwertyuiop[]\\", "QWERTYUIOP{}|"),
+    ("asdfghjkl;'", "ASDFGHJKL:\""),
+    (" zxcvbnm,./", " ZXCVBNM<>?"),
+];
+static AZERTY: [(&str, &str); 4] = [
+    ("²&é\"'(-è_çà)=", " 1234567890°+"),
+    ("azertyuiop $", "AZERTYUIOP £"),
+    ("qsdfghjklmù*", "QSDFGHJKLM%µ"),
+    ("<wxcvbn,;:!", ">WXCVBN?./§"),
+];
+static QWERTZ: [(&str, &str); 4] = [
+    (" 1234567890ß ", "°!\"§$%&/()=? "),
+    ("qwertzuiopü+", "QWERTZUIOPÜ*"),
+    ("asdfghjklöä#", "ASDFGHJKLÖÄ'"),
+    ("<yxcvbnm,.-", ">YXCVBNM;:_"),
+];
+static DVORAK: [(&str, &str); 4] = [
+    ("`1234567890[]", "~!@#$%^&*(){}"),
+    ("',.pyfgcrl/=\\", "\"<>PYFGCRL?+|"),
+    ("aoeuidhtns-", "AOEUIDHTNS_"),
+    (" ;qjkxbmwvz", " :QJKXBMWVZ"),
+];
+
+impl Layout {
+    // Returns the row and column of the key typing `c` without AltGr or a dead key, if any.
+    fn key(&self, c: char) -> Option<(usize, usize)> {
+        let rows = match self {
+            Layout::Qwerty => &QWERTY,
+            Layout::Azerty => &AZERTY,
+            Layout::Qwertz => &QWERTZ,
+            Layout::Dvorak => &DVORAK,
+            Layout::Mobile => return None,
+        };
+
+        rows.iter()
+            .enumerate()
+            .find_map(|(row, (base, shift))| {
+                base.chars()
+                    .position(|key| key == c)
+                    .or_else(|| shift.chars().position(|key| key == c))
+                    .map(|column| (row, column))
+            })
+            .filter(|_| c != ' ')
+    }
+}
+
+/**
+Checks whether `c` is typed the same way on every layout of `layouts`: on the base or shift layer of the
+same key of every physical keyboard, and on the letter layer of a mobile keyboard, which only has ASCII
+letters. Every character is safe when no layout is given.
+
+# Examples
+
+```
+use mcore::mstd::layout::{is_safe, Layout};
+
+let layouts = [Layout::Azerty, Layout::Qwertz];
+
+assert!(is_safe(&layouts, 'x'));
+assert!(is_safe(&layouts, '7'));
+assert!(!is_safe(&layouts, 'y'));
+assert!(!is_safe(&layouts, '@'));
+assert!(!is_safe(&[Layout::Mobile], '7'));
+```
+*/
+pub fn is_safe(layouts: &[Layout], c: char) -> bool {
+    if layouts.contains(&Layout::Mobile) && !c.is_ascii_alphabetic() {
+        return false;
+    }
+
+    let mut keys = layouts
+        .iter()
+        .filter(|layout| **layout != Layout::Mobile)
+        .map(|layout| layout.key(c));
+
+    match keys.next() {
+        Some(Some(first)) => keys.all(|key| key == Some(first)),
+        Some(None) => false,
+        None => true,
+    }
+}
+
+impl Codec<Layout> for Layout {
+    fn encode(&self) -> String {
+        match self {
+            Layout::Qwerty => "qwerty",
+            Layout::Azerty => "azerty",
+            Layout::Qwertz => "qwertz",
+            Layout::Dvorak => "dvorak",
+            Layout::Mobile => "mobile",
+        }
+        .to_string()
+    }
+
+    fn decode(s: Option<&str>) -> Result<Layout, DecodeError> {
+        let s = s.ok_or_else(|| {
+            DecodeError::new(
+                UnableToDecodeT,
+                "Unable to decode because the Layout is empty".to_owned(),
+            )
+        })?;
+
+        match s.trim().to_lowercase().as_str() {
+            "qwerty" | "us" => Ok(Layout::Qwerty),
+            "azerty" | "fr" => Ok(Layout::Azerty),
+            "qwertz" | "de" => Ok(Layout::Qwertz),
+            "dvorak" => Ok(Layout::Dvorak),
+            "mobile" => Ok(Layout::Mobile),
+            _ => Err(DecodeError::new(
+                UnableToDecodeT,
+                format!("Unable to decode {} to a keyboard layout", s),
+            )),
+        }
+    }
+
+    fn is(s: String) -> bool {
+        Layout::decode(Some(&s)).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mstd::param::{DIGITS, LOWER, SYMBOLS};
+
+    #[test]
+    fn test_layout_rows_line_up() {
+        for rows in [&QWERTY, &AZERTY, &QWERTZ, &DVORAK] {
+            for (base, shift) in rows.iter() {
+                assert_eq!(base.chars().count(), shift.chars().count(), "{}", base);
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_safe_across_french_and_german_keyboards() {
+        let layouts = [Layout::Azerty, Layout::Qwertz];
+        let safe = |set: &str| {
+            set.chars()
+                .filter(|c| is_safe(&layouts, *c))
+                .collect::<String>()
+        };
+
+        assert_eq!(safe(LOWER), "bcdefghijklnoprstuvx");
+        assert_eq!(safe(DIGITS), DIGITS);
+        assert_eq!(safe(SYMBOLS), ":;<>");
+        assert!(!is_safe(&[Layout::Qwertz], '^'));
+    }
+
+    #[test]
+    fn test_is_safe_with_a_single_layout() {
+        assert!(is_safe(&[Layout::Azerty], 'é'));
+        assert!(!is_safe(&[Layout::Qwerty], 'é'));
+        assert!(is_safe(&[Layout::Dvorak], 'z'));
+        assert!(!is_safe(&[Layout::Dvorak, Layout::Qwerty], 'z'));
+        assert!(is_safe(&[], '€'));
+    }
+
+    #[test]
+    fn test_layout_round_trip() {
+        for layout in [
+            Layout::Qwerty,
+            Layout::Azerty,
+            Layout::Qwertz,
+            Layout::Dvorak,
+            Layout::Mobile,
+        ] {
+            assert_eq!(Layout::decode(Some(&layout.encode())).unwrap(), layout);
+        }
+        assert!(Layout::decode(Some("colemak")).is_err());
+    }
+}
@@ -1,13 +1,21 @@
 pub mod cmd;
 pub mod cmds;
 pub mod codec;
+pub mod encoding;
 pub mod error;
+pub mod layout;
+pub mod otpauth;
 pub mod param;
+pub mod rules;
+pub mod store;
 
 use std::io::{BufRead, Error, Write};
 
-use self::cmd::{Execute, Input, Parse};
-use self::cmds::Generate;
+use self::cmd::{Execute, Input, Parse, Stream};
+use self::cmds::{
+    Add, Breach, Cert, Check, Derive, Generate, Get, History, Init, Keyfile, Keygen, List, Otp,
+    Passwd, Redeem, Remove, Restore, Verify,
+};
 use self::error::ErrorCode::UnableToReadInput;
 use self::error::GetInputError;
 
@@ -52,12 +60,9 @@ write_line_to(&mut output, "Hello World!").unwrap();
 ```
 */
 pub fn write_line_to<W: Write>(mut writer: W, line: &str) -> Result<(), Error> {
-    writer.write_all(line.as_bytes()).map_err(|e| {
-        Error::new(
-            std::io::ErrorKind::Other,
-            format!("Unable to write to stdout: {}", e),
-        )
-    })
+    writer
+        .write_all(line.as_bytes())
+        .map_err(|e| Error::other(format!("Unable to write to stdout: {}", e)))
 }
 
 /**
@@ -88,8 +93,9 @@ pub fn deserialize(args_vec: &str) -> Result<Input, String> {
     // return an Input struct like this: Input { command: "generate", params: { "--password": "1234", "--user": "test" } }
     // check that the first argument is not empty since it is required
 
-    let mut iter = args_vec.split_whitespace();
-    let command = iter.next().unwrap_or_default();
+    let args = split_args(args_vec)?;
+    let mut iter = args.into_iter();
+    let (command, _) = iter.next().unwrap_or_default();
 
     if command.is_empty() {
         return Err("No command was provided".to_string());
@@ -97,18 +103,26 @@ pub fn deserialize(args_vec: &str) -> Result<Input, String> {
 
     // generate --password --url www.google.com --username test
     let mut params = std::collections::HashMap::new();
+    let mut values = Vec::new();
     let mut key = String::new();
     let mut value = String::new();
 
-    for arg in iter {
-        if arg.starts_with("--") || arg.starts_with('-') {
+    for (arg, quoted) in iter {
+        // a lone dash or dashes followed by punctuation, like `--separator -`, are values rather than flags
+        let is_flag = arg
+            .trim_start_matches('-')
+            .starts_with(|c: char| c.is_alphabetic());
+
+        if arg.starts_with('-') && is_flag && !quoted {
             if !key.is_empty() {
                 params.insert(key, value);
             }
-            key = arg.to_string();
+            key = arg;
             value = String::new();
+        } else if key.is_empty() {
+            values.push(arg);
         } else {
-            value = arg.to_string();
+            value = arg;
         }
     }
 
@@ -117,11 +131,48 @@ pub fn deserialize(args_vec: &str) -> Result<Input, String> {
     }
 
     Ok(Input {
-        arg: command.to_string(),
+        arg: command,
         params,
+        values,
     })
 }
 
+// Splits on whitespace, keeping text between single or double quotes together. Inside double quotes a `\`
+// escapes the next character. Every argument is returned along with whether it was quoted.
+fn split_args(line: &str) -> Result<Vec<(String, bool)>, String> {
+    let mut args = Vec::new();
+    let mut current: Option<(String, bool)> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                let arg = current.get_or_insert_with(|| (String::new(), true));
+                arg.1 = true;
+                loop {
+                    match chars.next() {
+                        Some(end) if end == c => break,
+                        Some('\\') if c == '"' => match chars.next() {
+                            Some(escaped) => arg.0.push(escaped),
+                            None => return Err("Unterminated escape in the command".to_string()),
+                        },
+                        Some(other) => arg.0.push(other),
+                        None => return Err(format!("Unterminated quote {} in the command", c)),
+                    }
+                }
+            }
+            c if c.is_whitespace() => args.extend(current.take()),
+            c => current
+                .get_or_insert_with(|| (String::new(), false))
+                .0
+                .push(c),
+        }
+    }
+    args.extend(current.take());
+
+    Ok(args)
+}
+
 /**
 This function is used to match the input to a command and execute it.
 
@@ -138,22 +189,74 @@ let input = Input {
             ("--password".to_string(), "".to_string()),
             ("--username".to_string(), "test".to_string()),
         ]),
+        values: vec![],
     };
-let command = matcher(&input);
+let command = matcher(&input).unwrap();
+let fields = command.split('\t').collect::<Vec<&str>>();
 
-assert_eq!(command.unwrap().len(), 16);
+assert_eq!(fields[0], "test");
+assert_eq!(fields[1].len(), 16);
+assert!(fields[2].ends_with(" bits"));
 ```
 */
 pub fn matcher(input: &Input) -> Result<String, String> {
+    match input.arg.as_str() {
+        "generate" => run(Generate, input),
+        "derive" => run(Derive, input),
+        "check" => run(Check, input),
+        "breach" => run(Breach, input),
+        "otp" => run(Otp, input),
+        "verify" => run(Verify, input),
+        "redeem" => run(Redeem, input),
+        "keygen" => run(Keygen, input),
+        "cert" => run(Cert, input),
+        "init" => run(Init, input),
+        "add" => run(Add, input),
+        "get" => run(Get, input),
+        "list" => run(List, input),
+        "rm" => run(Remove, input),
+        "history" => run(History, input),
+        "restore" => run(Restore, input),
+        "passwd" => run(Passwd, input),
+        "keyfile" => run(Keyfile, input),
+        _ => Err(format!("Command {} not found", input.arg)),
+    }
+}
+
+// Parses the input with `command` and executes it with the params.
+fn run<P, C: Parse<P> + Execute<P>>(command: C, input: &Input) -> Result<String, String> {
+    command
+        .parse(input)
+        .and_then(|params| command.execute(Some(params)))
+}
+
+/**
+Works like `matcher`, but writes the output to `writer` as it is produced, so commands that stream many
+results, like `generate --count`, never hold them all in memory.
+
+# Examples
+
+```
+use mcore::mstd::{deserialize, matcher_to};
+
+let input = deserialize("generate --password 12 --count 3").unwrap();
+let mut output = Vec::new();
+matcher_to(&input, &mut output).unwrap();
+
+assert_eq!(String::from_utf8(output).unwrap().lines().count(), 3);
+```
+*/
+pub fn matcher_to<W: Write>(input: &Input, writer: W) -> Result<(), String> {
     match input.arg.as_str() {
         "generate" => {
             let command = Generate;
             match command.parse(input) {
-                Ok(params) => command.execute(Some(params)),
+                Ok(params) => command.stream(Some(params), writer),
                 Err(e) => Err(e),
             }
         }
-        _ => Err(format!("Command {} not found", input.arg)),
+        _ => matcher(input)
+            .and_then(|output| write_line_to(writer, &output).map_err(|e| e.to_string())),
     }
 }
 
@@ -205,6 +308,43 @@ mod tests {
         assert_eq!(command.unwrap().params.get("--username").unwrap(), "test");
     }
 
+    #[test]
+    fn test_prepare_input_keeps_the_values_before_the_first_flag() {
+        let command =
+            super::deserialize("otp 'otpauth://totp/a?secret=b' extra --digits 8").unwrap();
+
+        assert_eq!(command.values, vec!["otpauth://totp/a?secret=b", "extra"]);
+        assert_eq!(command.params.get("--digits").unwrap(), "8");
+    }
+
+    #[test]
+    fn test_prepare_input_keeps_dashes_as_values() {
+        let input = "generate --words 6 --separator - --capitalize";
+        let command = super::deserialize(input).unwrap();
+
+        assert_eq!(command.params.get("--separator").unwrap(), "-");
+        assert_eq!(command.params.get("--capitalize").unwrap(), "");
+    }
+
+    #[test]
+    fn test_prepare_input_keeps_quoted_values_together() {
+        let input =
+            r#"generate --rules "minlength: 12; allowed: [-_!];" --pattern '-9 9' --custom \"a"b"#;
+        let command = super::deserialize(input).unwrap();
+
+        assert_eq!(
+            command.params.get("--rules").unwrap(),
+            "minlength: 12; allowed: [-_!];"
+        );
+        assert_eq!(command.params.get("--pattern").unwrap(), "-9 9");
+        assert_eq!(command.params.get("--custom").unwrap(), "\\ab");
+    }
+
+    #[test]
+    fn test_prepare_input_is_err_when_a_quote_is_unterminated() {
+        assert!(super::deserialize("generate --rules \"minlength: 12;").is_err());
+    }
+
     #[test]
     fn test_prepare_input_is_err_when_input_is_empty() {
         let input = "";
@@ -225,12 +365,347 @@ mod tests {
                 ("--password".to_string(), "".to_string()),
                 ("--username".to_string(), "test".to_string()),
             ]),
+            values: vec![],
         };
         let command = matcher(&input);
         println!("{:?}", command);
 
         assert!(command.is_ok());
-        assert_eq!(command.unwrap().len(), 16);
+
+        let command = command.unwrap();
+        let fields = command.split('\t').collect::<Vec<&str>>();
+        assert_eq!(fields[0], "test");
+        assert_eq!(fields[1].len(), 16);
+    }
+
+    #[test]
+    fn test_matcher_to_streams_nul_separated_secrets() {
+        let input = super::deserialize("generate --password 8 --count 500 --null").unwrap();
+        let mut output = Vec::new();
+
+        assert!(super::matcher_to(&input, &mut output).is_ok());
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.split('\0').count(), 500);
+        assert!(output.split('\0').all(|record| record.len() == 8));
+    }
+
+    #[test]
+    fn test_matcher_generates_passwords_that_comply_with_the_rules() {
+        use super::{codec::Codec, rules::PasswordRules};
+
+        let rules =
+            "minlength: 20; required: upper; required: digit; allowed: [-_]; max-consecutive: 1;";
+        let input =
+            super::deserialize(&format!("generate --password 8 --rules \"{}\"", rules)).unwrap();
+        let output = super::matcher(&input).unwrap();
+        let password = output.split('\t').next().unwrap();
+
+        assert_eq!(password.len(), 20);
+        assert!(PasswordRules::decode(Some(rules))
+            .unwrap()
+            .policy
+            .is_satisfied_by(password));
+    }
+
+    #[test]
+    fn test_matcher_generates_strong_pins() {
+        use crate::generator::pin::is_weak;
+
+        let input = super::deserialize("generate --pin 6 --count 20").unwrap();
+        let output = super::matcher(&input).unwrap();
+
+        for line in output.lines() {
+            let pin = line.split('\t').next().unwrap();
+            assert_eq!(pin.len(), 6);
+            assert!(pin.chars().all(|c| c.is_ascii_digit()));
+            assert!(!is_weak(pin));
+        }
+    }
+
+    #[test]
+    fn test_matcher_generates_recovery_codes_that_can_be_redeemed() {
+        use super::{cmds::Redeem, param::RedeemParams, Execute};
+        use crate::generator::recovery::is_valid;
+
+        let input = super::deserialize("generate --recovery-codes").unwrap();
+        let output = super::matcher(&input).unwrap();
+        assert_eq!(output.lines().count(), 10);
+        assert!(output
+            .lines()
+            .all(|line| is_valid(line.split('\t').next().unwrap())));
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("recovery.txt");
+        std::fs::write(&file, &output).unwrap();
+        let redeem = |code: &str| {
+            Redeem.execute(Some(RedeemParams {
+                code: code.to_string(),
+                file: file.clone(),
+            }))
+        };
+
+        let code = output.lines().nth(3).unwrap().split('\t').next().unwrap();
+        assert_eq!(redeem(&code.to_lowercase()).unwrap(), "remaining: 9");
+        assert!(std::fs::read_to_string(&file)
+            .unwrap()
+            .contains(&format!("{}\tused", code)));
+        assert!(redeem(code).is_err());
+    }
+
+    #[test]
+    fn test_check_reports_the_strength_of_a_password() {
+        use super::{cmds::Check, param::CheckParams, Execute};
+
+        let output = Check
+            .execute(Some(CheckParams {
+                password: "mokuba1990".to_string(),
+                user_inputs: vec!["mokuba".to_string()],
+            }))
+            .unwrap();
+
+        assert!(output.lines().next().unwrap().starts_with("score: "));
+        assert!(output.contains("pattern: dictionary \"mokuba\", user input"));
+        assert!(output.contains("pattern: year \"1990\""));
+        assert_eq!(output.matches("crack time, ").count(), 4);
+        assert!(output.contains("suggestion: "));
+    }
+
+    #[test]
+    fn test_matcher_computes_one_time_passwords() {
+        // RFC 6238 secret "12345678901234567890" in base32
+        let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+        let input = super::deserialize(&format!("otp {} --time 59 --digits 8", secret)).unwrap();
+        assert_eq!(super::matcher(&input).unwrap(), "94287082\t1 seconds");
+
+        let uri = format!("otpauth://hotp/mokuba?secret={}&counter=1", secret);
+        let input = super::deserialize(&format!("otp '{}'", uri)).unwrap();
+        assert_eq!(super::matcher(&input).unwrap(), "287082\tcounter 1");
+    }
+
+    #[test]
+    fn test_matcher_generates_tokens_that_verify() {
+        let input = super::deserialize("generate --format hex --password 32").unwrap();
+        let output = super::matcher(&input).unwrap();
+        assert_eq!(
+            output,
+            format!("{}\t256.0 bits", output.split('\t').next().unwrap())
+        );
+        assert_eq!(output.split('\t').next().unwrap().len(), 64);
+
+        let input = super::deserialize("generate --prefix ghp --password 22").unwrap();
+        let output = super::matcher(&input).unwrap();
+        let token = output.split('\t').next().unwrap();
+        assert!(token.starts_with("ghp_"));
+
+        let input = super::deserialize(&format!("verify {}", token)).unwrap();
+        assert_eq!(super::matcher(&input).unwrap(), "valid: ghp");
+        let input = super::deserialize(&format!("verify {}x", token)).unwrap();
+        assert!(super::matcher(&input).is_err());
+    }
+
+    #[test]
+    fn test_cert_issues_a_pkcs12_bundle_signed_by_the_stored_ca() {
+        use super::{
+            cmds::Cert,
+            param::{BundleFormat, CertAction, CertParams, Kdf},
+            Execute,
+        };
+        use crate::vault::{load, save, Vault};
+        use rand::rngs::OsRng;
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let params = |action, name: &str, days| CertParams {
+            action,
+            name: name.to_string(),
+            ca: "dev".to_string(),
+            days,
+            format: BundleFormat::Pkcs12,
+            output: Some(dir.join("app")),
+            passphrase: Some("correct horse battery staple".to_string()),
+            store: dir.join("certs"),
+            master: "master".to_string(),
+            keyfile: None,
+            file: dir.join("vault"),
+        };
+
+        assert!(Cert
+            .execute(Some(params(CertAction::Ca, "dev", 3650)))
+            .is_err());
+        let vault = Vault::create("master", None, Kdf::new(64, 1, 1), &mut OsRng).unwrap();
+        save(&dir.join("vault"), &vault, true, &mut OsRng).unwrap();
+
+        let output = Cert
+            .execute(Some(params(CertAction::Ca, "dev", 3650)))
+            .unwrap();
+        assert_eq!(
+            output,
+            format!("ca: {}", dir.join("certs/dev.crt").display())
+        );
+
+        let issue = CertAction::Issue(vec!["localhost".to_string(), "127.0.0.1".to_string()]);
+        let output = Cert.execute(Some(params(issue, "app", 825))).unwrap();
+        assert!(output.contains(&format!("bundle: {}", dir.join("app.p12").display())));
+        assert!(output.ends_with("passphrase: correct horse battery staple"));
+        assert!(!dir.join("certs/app.key").exists());
+
+        let vault = load(&dir.join("vault"), "master", None).unwrap();
+        assert_eq!(vault.titles(), ["cert:app", "cert:dev"]);
+        let wrong = CertParams {
+            master: "wrong".to_string(),
+            ..params(CertAction::Ca, "other", 1)
+        };
+        assert!(Cert.execute(Some(wrong)).is_err());
+        assert!(!dir.join("certs/other.crt").exists());
+
+        let missing = CertParams {
+            ca: "missing".to_string(),
+            ..params(CertAction::Issue(vec!["localhost".to_string()]), "other", 1)
+        };
+        assert!(Cert.execute(Some(missing)).is_err());
+    }
+
+    #[test]
+    fn test_vault_commands_add_get_list_and_remove_entries() {
+        use super::{
+            cmds::{Add, Get, History, Init, List, Passwd, Remove, Restore},
+            param::{Kdf, VaultAction, VaultParams},
+            Execute,
+        };
+        use crate::{mstd::codec::Codec, vault::entry::Entry};
+        use rand::rngs::OsRng;
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("vault");
+        let params = |action, master: &str| {
+            Some(VaultParams {
+                action,
+                master: master.to_string(),
+                keyfile: None,
+                file: file.clone(),
+            })
+        };
+        let entry = |title: &str, password: &str| {
+            VaultAction::Add(Entry::new(
+                title.to_string(),
+                password.to_string(),
+                0,
+                &mut OsRng,
+            ))
+        };
+
+        let init = VaultAction::Init(Kdf::new(64, 1, 1), 10);
+        assert!(Init.execute(params(init.clone(), "master")).is_ok());
+        assert!(Init.execute(params(init, "master")).is_err());
+        assert_eq!(
+            Add.execute(params(entry("mail", "hunter2"), "master")),
+            Ok("added: mail".to_string())
+        );
+        assert!(Add.execute(params(entry("bank", "x"), "wrong")).is_err());
+        Add.execute(params(entry("Bank", "1234"), "master"))
+            .unwrap();
+
+        let get = |title: &str| Get.execute(params(VaultAction::Get(title.to_string()), "master"));
+        assert_eq!(get("MAIL"), Ok("hunter2".to_string()));
+        let show = Get
+            .execute(params(VaultAction::Show("mail".to_string()), "master"))
+            .unwrap();
+        let shown = Entry::decode(Some(&show)).unwrap();
+        assert_eq!((shown.title.as_str(), shown.created), ("mail", 0));
+        assert_eq!(get(&shown.id), Ok("hunter2".to_string()));
+
+        let update = VaultAction::Update("mail".to_string(), "hunter3".to_string());
+        assert_eq!(
+            Add.execute(params(update, "master")),
+            Ok("updated: mail".to_string())
+        );
+        let history = History
+            .execute(params(VaultAction::History("mail".to_string()), "master"))
+            .unwrap();
+        assert!(history.starts_with("1\treplaced 20"), "{}", history);
+        assert_eq!(
+            Restore.execute(params(
+                VaultAction::Restore("mail".to_string(), 1),
+                "master"
+            )),
+            Ok("restored: mail version 1".to_string())
+        );
+        assert_eq!(get("mail"), Ok("hunter2".to_string()));
+        assert_eq!(
+            List.execute(params(VaultAction::List, "master")),
+            Ok("Bank\nmail".to_string())
+        );
+        assert_eq!(
+            Remove.execute(params(VaultAction::Remove("mail".to_string()), "master")),
+            Ok("removed: mail".to_string())
+        );
+        assert!(get("mail").is_err());
+
+        let passwd = VaultAction::Passwd {
+            master: "new master".to_string(),
+            memory: Some(128),
+            iterations: None,
+            parallelism: None,
+            rotate: true,
+        };
+        let downgrade = VaultAction::Passwd {
+            master: "new master".to_string(),
+            memory: Some(8),
+            iterations: None,
+            parallelism: None,
+            rotate: true,
+        };
+        assert!(Passwd.execute(params(downgrade, "master")).is_err());
+        let output = Passwd.execute(params(passwd, "master")).unwrap();
+        assert!(output.ends_with("kdf: argon2id m=128 t=1 p=1\nkey: rotated"));
+        assert!(get("bank").is_err());
+        assert_eq!(
+            Get.execute(params(VaultAction::Get("bank".to_string()), "new master")),
+            Ok("1234".to_string())
+        );
+    }
+
+    #[test]
+    fn test_vault_commands_need_the_keyfile() {
+        use super::{
+            cmds::{Init, Keyfile, List},
+            param::{Kdf, KeyfileParams, VaultAction, VaultParams},
+            Execute,
+        };
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let (file, key, other) = (dir.join("vault"), dir.join("key"), dir.join("other"));
+        let params = |action, keyfile: Option<&std::path::PathBuf>| {
+            Some(VaultParams {
+                action,
+                master: "master".to_string(),
+                keyfile: keyfile.cloned(),
+                file: file.clone(),
+            })
+        };
+
+        let generate = |output: &std::path::PathBuf| {
+            Keyfile.execute(Some(KeyfileParams {
+                output: output.clone(),
+            }))
+        };
+        assert_eq!(generate(&key), Ok(format!("keyfile: {}", key.display())));
+        assert!(generate(&key).is_err());
+        generate(&other).unwrap();
+
+        let init = VaultAction::Init(Kdf::new(64, 1, 1), 10);
+        Init.execute(params(init, Some(&key))).unwrap();
+        assert!(List.execute(params(VaultAction::List, Some(&key))).is_ok());
+        assert!(List
+            .execute(params(VaultAction::List, Some(&other)))
+            .is_err());
+        assert!(List.execute(params(VaultAction::List, None)).is_err());
+        assert!(List
+            .execute(params(VaultAction::List, Some(&dir.join("missing"))))
+            .is_err());
     }
 
     #[test]
@@ -245,6 +720,7 @@ mod tests {
                 ("--password".to_string(), "".to_string()),
                 ("--username".to_string(), "test".to_string()),
             ]),
+            values: vec![],
         };
         let command = matcher(&input);
         println!("{:?}", command);
@@ -0,0 +1,280 @@
+use super::{
+    codec::Codec,
+    encoding::{base32_decode, base32_encode},
+    error::{DecodeError, ErrorCode::UnableToDecodeT},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+/// Time based codes change every `period` seconds, counter based ones every time the counter is increased.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtpKind {
+    Totp { period: u64 },
+    Hotp { counter: u64 },
+}
+
+/// A one-time password secret and how its codes are computed, as provisioned by an `otpauth://` URI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OtpAuth {
+    pub kind: OtpKind,
+    pub secret: Vec<u8>,
+    pub algorithm: Algorithm,
+    pub digits: u32,
+    pub issuer: Option<String>,
+    pub account: Option<String>,
+}
+
+impl Default for OtpAuth {
+    fn default() -> Self {
+        OtpAuth {
+            kind: OtpKind::Totp { period: 30 },
+            secret: vec![],
+            algorithm: Algorithm::Sha1,
+            digits: 6,
+            issuer: None,
+            account: None,
+        }
+    }
+}
+
+impl Codec<OtpAuth> for OtpAuth {
+    fn encode(&self) -> String {
+        let (kind, parameter) = match self.kind {
+            OtpKind::Totp { period } => ("totp", format!("period={}", period)),
+            OtpKind::Hotp { counter } => ("hotp", format!("counter={}", counter)),
+        };
+        let algorithm = match self.algorithm {
+            Algorithm::Sha1 => "SHA1",
+            Algorithm::Sha256 => "SHA256",
+            Algorithm::Sha512 => "SHA512",
+        };
+        let label = match (&self.issuer, &self.account) {
+            (Some(issuer), Some(account)) => format!("{}:{}", escape(issuer), escape(account)),
+            (Some(name), None) | (None, Some(name)) => escape(name),
+            (None, None) => String::new(),
+        };
+        let issuer = self
+            .issuer
+            .as_ref()
+            .map(|issuer| format!("&issuer={}", escape(issuer)))
+            .unwrap_or_default();
+
+        format!(
+            "otpauth://{}/{}?secret={}{}&algorithm={}&digits={}&{}",
+            kind,
+            label,
+            base32_encode(&self.secret),
+            issuer,
+            algorithm,
+            self.digits,
+            parameter
+        )
+    }
+
+    /**
+    Decodes an `otpauth://totp/<label>?secret=<base32>` or `otpauth://hotp/<label>?secret=<base32>&counter=<n>`
+    URI, with the optional `issuer`, `algorithm` (SHA1, SHA256 or SHA512), `digits` (6 to 8) and `period`
+    parameters. A bare base32 secret is decoded as a time based secret with the default parameters: SHA1, 6
+    digits and 30 seconds.
+
+    # Examples
+
+    ```
+    use mcore::mstd::{codec::Codec, otpauth::{Algorithm, OtpAuth, OtpKind}};
+
+    let auth = OtpAuth::decode(Some(
+        "otpauth://totp/ACME%20Co:john@example.com?secret=JBSWY3DPEHPK3PXP&issuer=ACME%20Co&algorithm=SHA256&digits=8",
+    )).unwrap();
+
+    assert_eq!(auth.kind, OtpKind::Totp { period: 30 });
+    assert_eq!(auth.algorithm, Algorithm::Sha256);
+    assert_eq!(auth.digits, 8);
+    assert_eq!(auth.issuer.as_deref(), Some("ACME Co"));
+    assert_eq!(auth.account.as_deref(), Some("john@example.com"));
+    assert_eq!(OtpAuth::decode(Some("JBSW Y3DP EHPK 3PXP")).unwrap().secret, auth.secret);
+    ```
+    */
+    fn decode(s: Option<&str>) -> Result<OtpAuth, DecodeError> {
+        let s = s
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| {
+                error("Unable to decode because the one-time password secret is empty".to_owned())
+            })?;
+
+        let uri = match s.get(..10) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("otpauth://") => &s[10..],
+            _ => {
+                return secret(s).map(|secret| OtpAuth {
+                    secret,
+                    ..Default::default()
+                })
+            }
+        };
+
+        let (path, query) = uri.split_once('?').unwrap_or((uri, ""));
+        let (kind, label) = path.split_once('/').unwrap_or((path, ""));
+        let label = unescape(label)?;
+        let (mut issuer, account) = match label.split_once(':') {
+            Some((issuer, account)) => (Some(issuer.trim().to_string()), account.trim()),
+            None => (None, label.trim()),
+        };
+
+        let mut auth = OtpAuth {
+            account: Some(account.to_string()).filter(|account| !account.is_empty()),
+            ..Default::default()
+        };
+        let (mut period, mut counter) = (None, None);
+
+        for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+            let value = unescape(value)?;
+            match key.to_ascii_lowercase().as_str() {
+                "secret" => auth.secret = secret(&value)?,
+                "issuer" => issuer = Some(value).filter(|issuer| !issuer.is_empty()),
+                "algorithm" => {
+                    auth.algorithm = match value.to_ascii_uppercase().as_str() {
+                        "SHA1" => Algorithm::Sha1,
+                        "SHA256" => Algorithm::Sha256,
+                        "SHA512" => Algorithm::Sha512,
+                        _ => return Err(error(format!("Unsupported algorithm {}", value))),
+                    }
+                }
+                "digits" => auth.digits = u32::decode(Some(&value))?,
+                "period" => period = Some(u64::decode(Some(&value))?),
+                "counter" => counter = Some(u64::decode(Some(&value))?),
+                _ => (),
+            }
+        }
+        auth.issuer = issuer;
+
+        auth.kind = match (kind.to_ascii_lowercase().as_str(), period, counter) {
+            ("totp", Some(0), _) => {
+                return Err(error("The period must be greater than 0".to_owned()))
+            }
+            ("totp", period, _) => OtpKind::Totp {
+                period: period.unwrap_or(30),
+            },
+            ("hotp", _, Some(counter)) => OtpKind::Hotp { counter },
+            ("hotp", _, None) => return Err(error("Counter based URIs need a counter".to_owned())),
+            (kind, _, _) => {
+                return Err(error(format!(
+                    "Unsupported one-time password type {}",
+                    kind
+                )))
+            }
+        };
+
+        if auth.secret.is_empty() {
+            return Err(error(format!("The URI {} has no secret", s)));
+        }
+        if !(6..=8).contains(&auth.digits) {
+            return Err(error(format!(
+                "Codes have 6 to 8 digits, not {}",
+                auth.digits
+            )));
+        }
+
+        Ok(auth)
+    }
+
+    fn is(s: String) -> bool {
+        OtpAuth::decode(Some(&s)).is_ok()
+    }
+}
+
+fn secret(s: &str) -> Result<Vec<u8>, DecodeError> {
+    match base32_decode(s)? {
+        secret if secret.is_empty() => Err(error("The secret is empty".to_owned())),
+        secret => Ok(secret),
+    }
+}
+
+// Percent-decodes a part of the URI.
+fn unescape(s: &str) -> Result<String, DecodeError> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+
+    while let Some(byte) = iter.next() {
+        match byte {
+            b'%' => {
+                let hex = [iter.next(), iter.next()]
+                    .into_iter()
+                    .collect::<Option<Vec<u8>>>()
+                    .and_then(|hex| String::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(&hex, 16).ok())
+                    .ok_or_else(|| error(format!("Invalid percent-encoding in {}", s)))?;
+                bytes.push(hex);
+            }
+            byte => bytes.push(byte),
+        }
+    }
+
+    String::from_utf8(bytes).map_err(|_| error(format!("Invalid UTF-8 in {}", s)))
+}
+
+// Percent-encodes everything but the unreserved characters of RFC 3986 and `@`.
+fn escape(s: &str) -> String {
+    s.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'@' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn error(cause: String) -> DecodeError {
+    DecodeError::new(UnableToDecodeT, cause)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_otpauth_round_trip() {
+        let auth = OtpAuth {
+            kind: OtpKind::Hotp { counter: 42 },
+            secret: b"12345678901234567890".to_vec(),
+            algorithm: Algorithm::Sha512,
+            digits: 7,
+            issuer: Some("Mokuba & Co".to_string()),
+            account: Some("alice@example.com".to_string()),
+        };
+
+        assert_eq!(OtpAuth::decode(Some(&auth.encode())).unwrap(), auth);
+    }
+
+    #[test]
+    fn test_otpauth_defaults() {
+        let auth = OtpAuth::decode(Some("otpauth://totp/alice?secret=JBSWY3DPEHPK3PXP")).unwrap();
+
+        assert_eq!(auth.kind, OtpKind::Totp { period: 30 });
+        assert_eq!(auth.algorithm, Algorithm::Sha1);
+        assert_eq!(auth.digits, 6);
+        assert_eq!(auth.issuer, None);
+        assert_eq!(auth.account.as_deref(), Some("alice"));
+    }
+
+    #[test]
+    fn test_otpauth_is_err_when_invalid() {
+        for uri in [
+            "otpauth://totp/a?secret=",
+            "otpauth://totp/a?secret=JBSWY3DP&digits=9",
+            "otpauth://totp/a?secret=JBSWY3DP&period=0",
+            "otpauth://totp/a?secret=JBSWY3DP&algorithm=MD5",
+            "otpauth://hotp/a?secret=JBSWY3DP",
+            "otpauth://motp/a?secret=JBSWY3DP",
+            "otpauth://totp/a%2?secret=JBSWY3DP",
+            "not base32!",
+        ] {
+            assert!(OtpAuth::decode(Some(uri)).is_err(), "{}", uri);
+        }
+    }
+}
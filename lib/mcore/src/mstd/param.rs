@@ -1,9 +1,370 @@
 use mmacro::ConstructorM;
+use std::path::PathBuf;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
 
-#[derive(Debug, ConstructorM)]
+use super::{
+    codec::Codec,
+    error::{DecodeError, ErrorCode::UnableToDecodeT},
+    layout::{self, Layout},
+    otpauth::OtpAuth,
+};
+use crate::vault::entry::Entry;
+
+pub static UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+pub static LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+pub static DIGITS: &str = "0123456789";
+// Quotes, backslash and backtick are left out since they tend to break shells, CSV files and forms.
+pub static SYMBOLS: &str = "!#$%&()*+,-./:;<=>?@[]^_{|}~";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CharClass {
+    Upper,
+    Lower,
+    Digits,
+    Symbols,
+    Custom(String),
+}
+
+impl CharClass {
+    /// Returns the characters of the class, without the ones in `exclude`.
+    pub fn chars(&self, exclude: &str) -> Vec<char> {
+        let set = match self {
+            CharClass::Upper => UPPER,
+            CharClass::Lower => LOWER,
+            CharClass::Digits => DIGITS,
+            CharClass::Symbols => SYMBOLS,
+            CharClass::Custom(set) => set.as_str(),
+        };
+
+        let mut chars = set
+            .chars()
+            .filter(|c| !exclude.contains(*c))
+            .collect::<Vec<char>>();
+        chars.sort_unstable();
+        chars.dedup();
+        chars
+    }
+}
+
+/// A character class together with the minimum amount of characters that must be drawn from it.
+#[derive(Debug, Clone, PartialEq, Eq, ConstructorM)]
+pub struct ClassRule {
+    pub class: CharClass,
+    pub min: u32,
+}
+
+impl Codec<ClassRule> for ClassRule {
+    fn encode(&self) -> String {
+        let name = match &self.class {
+            CharClass::Upper => "upper",
+            CharClass::Lower => "lower",
+            CharClass::Digits => "digits",
+            CharClass::Symbols => "symbols",
+            CharClass::Custom(_) => "custom",
+        };
+        format!("{}:{}", name, self.min)
+    }
+
+    /**
+    Decodes a rule written as `<class>[:<min>]`, where the minimum defaults to 1. A `custom` class is
+    decoded with an empty set, which has to be filled by the caller.
+
+    # Examples
+
+    ```
+    use mcore::mstd::{codec::Codec, param::{CharClass, ClassRule}};
+
+    assert_eq!(ClassRule::decode(Some("digits:2")).unwrap(), ClassRule::new(CharClass::Digits, 2));
+    assert_eq!(ClassRule::decode(Some("upper")).unwrap(), ClassRule::new(CharClass::Upper, 1));
+    assert!(ClassRule::decode(Some("emoji")).is_err());
+    ```
+    */
+    fn decode(s: Option<&str>) -> Result<ClassRule, DecodeError> {
+        let s = s.ok_or_else(|| {
+            DecodeError::new(
+                UnableToDecodeT,
+                "Unable to decode because the ClassRule is empty".to_owned(),
+            )
+        })?;
+
+        let (name, min) = match s.split_once(':') {
+            Some((name, min)) => (name, u32::decode(Some(min))?),
+            None => (s, 1),
+        };
+
+        let class = match name.trim().to_lowercase().as_str() {
+            "upper" => CharClass::Upper,
+            "lower" => CharClass::Lower,
+            "digits" => CharClass::Digits,
+            "symbols" => CharClass::Symbols,
+            "custom" => CharClass::Custom(String::new()),
+            _ => {
+                return Err(DecodeError::new(
+                    UnableToDecodeT,
+                    format!("Unable to decode {} to a character class", name),
+                ))
+            }
+        };
+
+        Ok(ClassRule::new(class, min))
+    }
+
+    fn is(s: String) -> bool {
+        ClassRule::decode(Some(&s)).is_ok()
+    }
+}
+
+/// Describes which characters a password may contain and how many of each class it needs. The maximum
+/// amount of identical consecutive characters is only enforced by the password mode.
+///
+/// The password mode draws symbols: the Unicode scalar values of the sets, or their grapheme clusters with
+/// `graphemes`, so `é` written as `e` and a combining accent stays one character. With `nfc`, the sets are
+/// normalized to NFC before they are split into grapheme clusters, and the clusters that would compose with
+/// the symbol drawn before them, like a lone combining mark, are left out.
+///
+/// With `layouts`, the password, pronounceable and pattern modes and `derive` only draw characters typed the
+/// same way on all of them, see `layout::is_safe`. The other modes have fixed alphabets and refuse layouts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Policy {
+    pub rules: Vec<ClassRule>,
+    pub exclude: String,
+    pub max_consecutive: Option<u32>,
+    pub graphemes: bool,
+    pub nfc: bool,
+    pub layouts: Vec<Layout>,
+}
+
+impl Policy {
+    pub fn new(rules: Vec<ClassRule>, exclude: String) -> Policy {
+        Policy {
+            rules,
+            exclude,
+            max_consecutive: None,
+            graphemes: false,
+            nfc: false,
+            layouts: vec![],
+        }
+    }
+
+    /// Returns `exclude` along with every character of the classes that is not safe on `layouts`.
+    pub fn excluded(&self) -> String {
+        let custom = self.rules.iter().filter_map(|rule| match &rule.class {
+            CharClass::Custom(set) => Some(set.as_str()),
+            _ => None,
+        });
+
+        let mut exclude = self.exclude.clone();
+        exclude.extend(
+            [UPPER, LOWER, DIGITS, SYMBOLS]
+                .into_iter()
+                .chain(custom)
+                .flat_map(str::chars)
+                .filter(|c| !layout::is_safe(&self.layouts, *c)),
+        );
+        exclude
+    }
+
+    /// Splits `s` into the symbols of the policy: scalar values, or grapheme clusters of `s` normalized to NFC
+    /// with `graphemes` or `nfc`.
+    pub fn split(&self, s: &str) -> Vec<String> {
+        let normalized = match self.nfc {
+            true => s.nfc().collect::<String>(),
+            false => s.to_string(),
+        };
+        let s = normalized.as_str();
+        let symbols: Box<dyn Iterator<Item = &str>> = match self.graphemes || self.nfc {
+            true => Box::new(s.graphemes(true)),
+            false => Box::new(s.char_indices().map(|(i, c)| &s[i..i + c.len_utf8()])),
+        };
+
+        symbols.map(str::to_string).collect()
+    }
+
+    /// Returns the symbols of `class`, without the excluded and unsafe ones, deduplicated and sorted.
+    pub fn symbols_of(&self, class: &CharClass) -> Vec<String> {
+        let set = match class {
+            CharClass::Custom(set) => set.clone(),
+            class => class.chars("").into_iter().collect(),
+        };
+        let exclude = self.split(&self.exclude);
+
+        let mut symbols = self
+            .split(&set)
+            .into_iter()
+            .filter(|symbol| !exclude.contains(symbol))
+            .filter(|symbol| symbol.chars().all(|c| layout::is_safe(&self.layouts, c)))
+            .filter(|symbol| !self.nfc || !symbol.starts_with(composes_with_previous))
+            .collect::<Vec<String>>();
+        symbols.sort_unstable();
+        symbols.dedup();
+        symbols
+    }
+
+    /// Every symbol allowed by the policy, deduplicated and sorted.
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols = self
+            .rules
+            .iter()
+            .flat_map(|rule| self.symbols_of(&rule.class))
+            .collect::<Vec<String>>();
+        symbols.sort_unstable();
+        symbols.dedup();
+        symbols
+    }
+
+    /// Every character allowed by the policy, deduplicated and sorted.
+    pub fn alphabet(&self) -> Vec<char> {
+        let mut chars = self
+            .rules
+            .iter()
+            .flat_map(|rule| rule.class.chars(&self.excluded()))
+            .collect::<Vec<char>>();
+        chars.sort_unstable();
+        chars.dedup();
+        chars
+    }
+
+    /// Sum of the minimum counts of every class.
+    pub fn required(&self) -> u32 {
+        self.rules.iter().map(|rule| rule.min).sum()
+    }
+
+    /// Checks whether `password` only uses allowed symbols and meets every minimum count.
+    pub fn is_satisfied_by(&self, password: &str) -> bool {
+        let alphabet = self.symbols();
+        let symbols = self.split(password);
+
+        symbols.iter().all(|symbol| alphabet.contains(symbol))
+            && self.rules.iter().all(|rule| {
+                let class = self.symbols_of(&rule.class);
+                symbols
+                    .iter()
+                    .filter(|symbol| class.contains(symbol))
+                    .count()
+                    >= rule.min as usize
+            })
+            && self.respects_max_consecutive(password)
+    }
+
+    /// Checks that no symbol of `password` is repeated more than `max_consecutive` times in a row.
+    pub fn respects_max_consecutive(&self, password: &str) -> bool {
+        let max = match self.max_consecutive {
+            Some(max) => max as usize,
+            None => return true,
+        };
+
+        self.split(password)
+            .windows(max + 1)
+            .all(|window| window.iter().any(|symbol| *symbol != window[0]))
+    }
+}
+
+// Whether `c` composes with the character before it under NFC: combining marks, and the vowel and trailing
+// jamo of Hangul syllables.
+fn composes_with_previous(c: char) -> bool {
+    is_combining_mark(c) || matches!(c, '\u{1160}'..='\u{11FF}' | '\u{D7B0}'..='\u{D7FF}')
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy {
+            rules: vec![
+                ClassRule::new(CharClass::Upper, 1),
+                ClassRule::new(CharClass::Lower, 1),
+                ClassRule::new(CharClass::Digits, 1),
+                ClassRule::new(CharClass::Symbols, 1),
+            ],
+            exclude: String::new(),
+            max_consecutive: None,
+            graphemes: false,
+            nfc: false,
+            layouts: vec![],
+        }
+    }
+}
+
+/// Settings of a diceware passphrase, made of words from the bundled EFF large wordlist.
+#[derive(Debug, Clone, PartialEq, Eq, ConstructorM)]
+pub struct Passphrase {
+    pub words: u32,
+    pub separator: String,
+    pub capitalize: bool,
+    pub digit: bool,
+    pub symbol: bool,
+}
+
+impl Default for Passphrase {
+    fn default() -> Self {
+        Passphrase {
+            words: 6,
+            separator: "-".to_string(),
+            capitalize: false,
+            digit: false,
+            symbol: false,
+        }
+    }
+}
+
+/// The kind of secret to generate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Password,
+    Passphrase(Passphrase),
+    Pronounceable,
+    Pattern(String),
+    Pin,
+    Token(TokenFormat),
+    /// One-time recovery codes like `ABCD-EFGH-JKLM`, with a check character
+    Recovery,
+}
+
+/// Encoding of a random token, whose length is its amount of random bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenFormat {
+    Hex,
+    Base32,
+    Base64Url,
+    /// A random UUID (version 4), always 122 random bits
+    Uuid,
+    /// A ULID, the current time in milliseconds followed by 80 random bits
+    Ulid,
+    /// An API token like `ghp_<base62 bytes><CRC32>`, whose checksum catches typos before any lookup
+    Prefixed(String),
+}
+
+/// Style of the username generated along with the password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Username {
+    /// `adjective-noun-number`, like `brave-otter-4821`
+    Words,
+    /// Lowercase letters and digits, starting with a letter
+    Random,
+    /// An alias on the given domain, like `brave.otter.4821@example.com`
+    Email(String),
+    /// A username chosen by the user, returned as-is
+    Fixed(String),
+}
+
+#[derive(Debug, Clone)]
 pub struct GenerateParams {
     pub length: u32,
-    pub username: Option<String>,
+    pub username: Option<Username>,
+    pub policy: Policy,
+    pub mode: Mode,
+    pub min_entropy: Option<u32>,
+    pub count: u32,
+    pub delimiter: char,
+}
+
+impl GenerateParams {
+    pub fn new(length: u32, username: Option<Username>) -> GenerateParams {
+        GenerateParams {
+            length,
+            username,
+            ..Default::default()
+        }
+    }
 }
 
 impl Default for GenerateParams {
@@ -11,6 +372,258 @@ impl Default for GenerateParams {
         GenerateParams {
             length: 10,
             username: None,
+            policy: Policy::default(),
+            mode: Mode::Password,
+            min_entropy: None,
+            count: 1,
+            delimiter: '\n',
+        }
+    }
+}
+
+/// Cost of the Argon2id key derivation: memory in KiB, amount of iterations and degree of parallelism.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ConstructorM)]
+pub struct Kdf {
+    pub memory: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for Kdf {
+    fn default() -> Self {
+        Kdf {
+            memory: 64 * 1024,
+            iterations: 3,
+            parallelism: 1,
         }
     }
 }
+
+/// Inputs of a stateless password derivation. The same params always derive the same password.
+#[derive(Debug, Clone)]
+pub struct DeriveParams {
+    pub master: String,
+    pub site: String,
+    pub login: String,
+    pub counter: u32,
+    pub length: u32,
+    pub policy: Policy,
+    pub kdf: Kdf,
+}
+
+impl Default for DeriveParams {
+    fn default() -> Self {
+        DeriveParams {
+            master: String::new(),
+            site: String::new(),
+            login: String::new(),
+            counter: 1,
+            length: 16,
+            policy: Policy::default(),
+            kdf: Kdf::default(),
+        }
+    }
+}
+
+/// A password to estimate the strength of, along with words related to its owner, like the site or the login,
+/// that an attacker would try first.
+#[derive(Debug, Clone, Default)]
+pub struct CheckParams {
+    pub password: String,
+    pub user_inputs: Vec<String>,
+}
+
+/// A password to look up in a local Pwned Passwords file.
+#[derive(Debug, Clone, Default)]
+pub struct BreachParams {
+    pub password: String,
+    pub file: PathBuf,
+}
+
+/// The kind of keypair made by `keygen`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyType {
+    /// An OpenSSH Ed25519 keypair, like `ssh-keygen -t ed25519`
+    Ed25519,
+    /// PEM encoded X25519 keys, like `openssl genpkey -algorithm X25519`
+    X25519,
+    /// Base64 X25519 keys, like `wg genkey | wg pubkey`
+    WireGuard,
+}
+
+/// A keypair to generate and the path of its private key, the public key is written next to it with `.pub`.
+/// Only Ed25519 private keys can be protected with a passphrase.
+#[derive(Debug, Clone)]
+pub struct KeygenParams {
+    pub kind: KeyType,
+    pub output: PathBuf,
+    pub comment: String,
+    pub passphrase: Option<String>,
+}
+
+/// What the `cert` command does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CertAction {
+    /// Creates a certificate authority
+    Ca,
+    /// Issues a certificate for the given DNS names and IP addresses, signed by the certificate authority
+    Issue(Vec<String>),
+}
+
+/// How an issued certificate and its key are exported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleFormat {
+    /// The certificate chain in `<output>.crt` and the key in `<output>.key`
+    Pem,
+    /// Both in `<output>.p12`, encrypted with a passphrase
+    Pkcs12,
+}
+
+/// A certificate to create under `name` in the `store` directory, with its key in the vault stored at `file`,
+/// unlocked with the `master` password and the `keyfile`, if the vault needs one. It is signed by the
+/// certificate authority named `ca` and exported to `output`. The passphrase is only used by PKCS#12 bundles.
+#[derive(Debug, Clone)]
+pub struct CertParams {
+    pub action: CertAction,
+    pub name: String,
+    pub ca: String,
+    pub days: u32,
+    pub format: BundleFormat,
+    pub output: Option<PathBuf>,
+    pub passphrase: Option<String>,
+    pub store: PathBuf,
+    pub master: String,
+    pub keyfile: Option<PathBuf>,
+    pub file: PathBuf,
+}
+
+/// What a vault command does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VaultAction {
+    /// Creates an empty vault whose master key is derived with the given cost, keeping the given amount of
+    /// previous passwords per entry
+    Init(Kdf, u32),
+    /// Adds an entry
+    Add(Entry),
+    /// Replaces the password of the entry with the given title by the given one
+    Update(String, String),
+    /// Returns the password of the entry with the given title
+    Get(String),
+    /// Returns every field of the entry with the given title, as encoded by its `Codec`
+    Show(String),
+    /// Lists the titles of the entries
+    List,
+    /// Removes the entry with the given title
+    Remove(String),
+    /// Lists when the previous passwords of the entry with the given title were replaced
+    History(String),
+    /// Puts back the given version of the password of the entry with the given title, 1 being the previous one
+    Restore(String, usize),
+    /// Seals the vault with a new master password, with the given costs in place of the current ones, and
+    /// under a new vault key with `rotate`
+    Passwd {
+        master: String,
+        memory: Option<u32>,
+        iterations: Option<u32>,
+        parallelism: Option<u32>,
+        rotate: bool,
+    },
+}
+
+/// A vault command on the vault stored at `file`, unlocked with the `master` password and the `keyfile`, if
+/// the vault needs one.
+#[derive(Debug, Clone)]
+pub struct VaultParams {
+    pub action: VaultAction,
+    pub master: String,
+    pub keyfile: Option<PathBuf>,
+    pub file: PathBuf,
+}
+
+/// The path of a new keyfile to write.
+#[derive(Debug, Clone)]
+pub struct KeyfileParams {
+    pub output: PathBuf,
+}
+
+/// A recovery code to mark as used in the set stored in `file`.
+#[derive(Debug, Clone, Default)]
+pub struct RedeemParams {
+    pub code: String,
+    pub file: PathBuf,
+}
+
+/// A one-time password secret and the time, in seconds since the Unix epoch, to compute its code at.
+#[derive(Debug, Clone, Default)]
+pub struct OtpParams {
+    pub auth: OtpAuth,
+    pub time: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alphabet_skips_excluded_characters() {
+        let policy = Policy::new(
+            vec![
+                ClassRule::new(CharClass::Digits, 1),
+                ClassRule::new(CharClass::Custom("abc1".to_string()), 0),
+            ],
+            "01".to_string(),
+        );
+
+        assert_eq!(
+            policy.alphabet().into_iter().collect::<String>(),
+            "23456789abc"
+        );
+    }
+
+    #[test]
+    fn test_policy_is_satisfied_by() {
+        let policy = Policy::default();
+
+        assert!(policy.is_satisfied_by("aB3!"));
+        assert!(!policy.is_satisfied_by("aB3d"));
+        assert!(!policy.is_satisfied_by("aB3!`"));
+    }
+
+    #[test]
+    fn test_policy_respects_max_consecutive() {
+        let policy = Policy {
+            max_consecutive: Some(2),
+            ..Policy::default()
+        };
+
+        assert!(policy.respects_max_consecutive("aab!1AAb"));
+        assert!(!policy.respects_max_consecutive("aaab"));
+        assert!(Policy::default().respects_max_consecutive("aaaa"));
+    }
+
+    #[test]
+    fn test_policy_splits_symbols() {
+        let custom = |graphemes, nfc| Policy {
+            graphemes,
+            nfc,
+            ..Policy::new(
+                vec![ClassRule::new(
+                    CharClass::Custom("e\u{301}日本".to_string()),
+                    1,
+                )],
+                String::new(),
+            )
+        };
+
+        assert_eq!(custom(false, false).symbols(), ["e", "\u{301}", "日", "本"]);
+        assert_eq!(custom(true, false).symbols(), ["e\u{301}", "日", "本"]);
+        assert_eq!(custom(true, true).symbols(), ["é", "日", "本"]);
+        assert!(custom(true, true).is_satisfied_by("e\u{301}"));
+    }
+
+    #[test]
+    fn test_class_rule_round_trip() {
+        let rule = ClassRule::new(CharClass::Symbols, 3);
+        assert_eq!(ClassRule::decode(Some(&rule.encode())).unwrap(), rule);
+    }
+}
@@ -0,0 +1,322 @@
+use super::{
+    codec::Codec,
+    error::{DecodeError, ErrorCode::UnableToDecodeT},
+    param::{CharClass, ClassRule, Policy, DIGITS, LOWER, UPPER},
+};
+
+// The `special` class of the spec, without the space since it does not survive most forms and shells.
+pub static SPECIAL: &str = "-~!@#$%^&*_+=`|(){}[:;\"'<>,.?]";
+
+/**
+Password rules in the `passwordrules` syntax used by browsers, e.g.
+`minlength: 12; required: lower; required: digit; allowed: [-_!]; max-consecutive: 2;`.
+
+Every `required` property becomes a class of which at least one character is needed, and every `allowed`
+property a class that may be used. When neither is present, every printable ASCII character is allowed.
+`unicode` is treated as `ascii-printable`, and unknown properties are ignored as the spec requires.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordRules {
+    pub policy: Policy,
+    pub min_length: Option<u32>,
+    pub max_length: Option<u32>,
+}
+
+impl PasswordRules {
+    /// Clamps `length` between the minimum and maximum length of the rules.
+    pub fn length(&self, length: u32) -> u32 {
+        let length = length.max(self.min_length.unwrap_or(0));
+        length.min(self.max_length.unwrap_or(u32::MAX))
+    }
+}
+
+impl Codec<PasswordRules> for PasswordRules {
+    fn encode(&self) -> String {
+        let mut properties = Vec::new();
+
+        if let Some(min) = self.min_length {
+            properties.push(format!("minlength: {};", min));
+        }
+        if let Some(max) = self.max_length {
+            properties.push(format!("maxlength: {};", max));
+        }
+        for rule in &self.policy.rules {
+            let property = match rule.min {
+                0 => "allowed",
+                _ => "required",
+            };
+            for _ in 0..rule.min.max(1) {
+                properties.push(format!("{}: {};", property, identifier(&rule.class)));
+            }
+        }
+        if let Some(max) = self.policy.max_consecutive {
+            properties.push(format!("max-consecutive: {};", max));
+        }
+
+        properties.join(" ")
+    }
+
+    /**
+    Decodes rules written in the `passwordrules` syntax.
+
+    # Examples
+
+    ```
+    use mcore::mstd::{codec::Codec, rules::PasswordRules};
+
+    let rules = PasswordRules::decode(Some(
+        "minlength: 12; required: lower; required: digit; allowed: [-_!]; max-consecutive: 2;",
+    ))
+    .unwrap();
+
+    assert_eq!(rules.min_length, Some(12));
+    assert_eq!(rules.policy.max_consecutive, Some(2));
+    assert_eq!(rules.policy.required(), 2);
+    assert!(PasswordRules::decode(Some("required: emoji;")).is_err());
+    ```
+    */
+    fn decode(s: Option<&str>) -> Result<PasswordRules, DecodeError> {
+        let s = s.filter(|s| !s.trim().is_empty()).ok_or_else(|| {
+            DecodeError::new(
+                UnableToDecodeT,
+                "Unable to decode because the password rules are empty".to_owned(),
+            )
+        })?;
+
+        let mut rules = Vec::new();
+        let mut min_length = None;
+        let mut max_length = None;
+        let mut max_consecutive = None;
+
+        for property in properties(s) {
+            let (name, value) = property.split_once(':').ok_or_else(|| {
+                DecodeError::new(
+                    UnableToDecodeT,
+                    format!("Unable to decode the property {}", property),
+                )
+            })?;
+            let value = value.trim();
+
+            match name.trim().to_lowercase().as_str() {
+                "minlength" => min_length = Some(u32::decode(Some(value))?),
+                "maxlength" => max_length = Some(u32::decode(Some(value))?),
+                "max-consecutive" => match u32::decode(Some(value))? {
+                    0 => {
+                        return Err(DecodeError::new(
+                            UnableToDecodeT,
+                            "max-consecutive must be greater than 0".to_owned(),
+                        ))
+                    }
+                    max => max_consecutive = Some(max),
+                },
+                "required" => rules.push(ClassRule::new(class(value)?, 1)),
+                "allowed" => rules.push(ClassRule::new(class(value)?, 0)),
+                _ => (),
+            }
+        }
+
+        if rules.is_empty() {
+            rules.push(ClassRule::new(CharClass::Custom(ascii_printable()), 0));
+        }
+
+        if let (Some(min), Some(max)) = (min_length, max_length) {
+            if min > max {
+                return Err(DecodeError::new(
+                    UnableToDecodeT,
+                    format!("minlength {} is greater than maxlength {}", min, max),
+                ));
+            }
+        }
+
+        Ok(PasswordRules {
+            policy: Policy {
+                max_consecutive,
+                ..Policy::new(rules, String::new())
+            },
+            min_length,
+            max_length,
+        })
+    }
+
+    fn is(s: String) -> bool {
+        PasswordRules::decode(Some(&s)).is_ok()
+    }
+}
+
+// Splits on `;`, except inside custom classes like `[;]`.
+fn properties(s: &str) -> Vec<String> {
+    let mut properties = vec![String::new()];
+    let mut in_class = false;
+    let mut previous = ' ';
+
+    for c in s.chars() {
+        match c {
+            '[' if !in_class => in_class = true,
+            // a `]` right after the `[` is part of the class
+            ']' if in_class && previous != '[' => in_class = false,
+            ';' if !in_class => {
+                properties.push(String::new());
+                previous = c;
+                continue;
+            }
+            _ => (),
+        }
+        previous = c;
+        if let Some(property) = properties.last_mut() {
+            property.push(c);
+        }
+    }
+
+    properties
+        .into_iter()
+        .map(|property| property.trim().to_string())
+        .filter(|property| !property.is_empty())
+        .collect()
+}
+
+// Decodes a comma separated list of identifiers and custom classes into a single class.
+fn class(value: &str) -> Result<CharClass, DecodeError> {
+    let mut sets = Vec::new();
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ',' => (),
+            c if c.is_whitespace() => (),
+            '[' => {
+                let mut set = String::new();
+                // a `]` right after the `[` is part of the class
+                if chars.peek() == Some(&']') {
+                    set.extend(chars.next());
+                }
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some(c) => set.push(c),
+                        None => {
+                            return Err(DecodeError::new(
+                                UnableToDecodeT,
+                                format!("Unterminated custom class in {}", value),
+                            ))
+                        }
+                    }
+                }
+                sets.push(set);
+            }
+            c => {
+                let mut name = c.to_string();
+                while let Some(c) = chars.next_if(|c| *c != ',' && !c.is_whitespace()) {
+                    name.push(c);
+                }
+                sets.push(named(&name)?);
+            }
+        }
+    }
+
+    match sets.as_slice() {
+        [] => Err(DecodeError::new(
+            UnableToDecodeT,
+            "Unable to decode an empty character class".to_owned(),
+        )),
+        [set] if set == UPPER => Ok(CharClass::Upper),
+        [set] if set == LOWER => Ok(CharClass::Lower),
+        [set] if set == DIGITS => Ok(CharClass::Digits),
+        _ => Ok(CharClass::Custom(sets.concat())),
+    }
+}
+
+fn named(name: &str) -> Result<String, DecodeError> {
+    match name.to_lowercase().as_str() {
+        "upper" => Ok(UPPER.to_string()),
+        "lower" => Ok(LOWER.to_string()),
+        "digit" => Ok(DIGITS.to_string()),
+        "special" => Ok(SPECIAL.to_string()),
+        "ascii-printable" | "unicode" => Ok(ascii_printable()),
+        _ => Err(DecodeError::new(
+            UnableToDecodeT,
+            format!("Unable to decode {} to a character class", name),
+        )),
+    }
+}
+
+fn identifier(class: &CharClass) -> String {
+    match class {
+        CharClass::Upper => "upper".to_string(),
+        CharClass::Lower => "lower".to_string(),
+        CharClass::Digits => "digit".to_string(),
+        CharClass::Symbols => format!("[{}]", super::param::SYMBOLS),
+        CharClass::Custom(set) => {
+            // a `]` is only valid as the first character of a custom class
+            let (closing, rest): (String, String) = set.chars().partition(|c| *c == ']');
+            format!("[{}{}]", closing, rest)
+        }
+    }
+}
+
+fn ascii_printable() -> String {
+    ('!'..='~').collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_required_and_allowed_classes() {
+        let rules = PasswordRules::decode(Some(
+            "minlength: 12; maxlength: 20; required: lower; required: upper, digit; allowed: [-_!];",
+        ))
+        .unwrap();
+
+        assert_eq!(rules.min_length, Some(12));
+        assert_eq!(rules.max_length, Some(20));
+        assert_eq!(
+            rules.policy.rules,
+            vec![
+                ClassRule::new(CharClass::Lower, 1),
+                ClassRule::new(CharClass::Custom(format!("{}{}", UPPER, DIGITS)), 1),
+                ClassRule::new(CharClass::Custom("-_!".to_string()), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_custom_classes_with_separators() {
+        let rules = PasswordRules::decode(Some("required: [];,]; allowed: special")).unwrap();
+
+        assert_eq!(
+            rules.policy.rules[0],
+            ClassRule::new(CharClass::Custom("];,".to_string()), 1)
+        );
+        assert_eq!(
+            rules.policy.rules[1],
+            ClassRule::new(CharClass::Custom(SPECIAL.to_string()), 0)
+        );
+    }
+
+    #[test]
+    fn test_decode_defaults_to_ascii_printable() {
+        let rules = PasswordRules::decode(Some("minlength: 8; unknown: 3;")).unwrap();
+
+        assert_eq!(rules.policy.alphabet().len(), 94);
+        assert_eq!(rules.length(4), 8);
+    }
+
+    #[test]
+    fn test_decode_fails_on_invalid_rules() {
+        assert!(PasswordRules::decode(Some("minlength: twelve;")).is_err());
+        assert!(PasswordRules::decode(Some("required: [abc;")).is_err());
+        assert!(PasswordRules::decode(Some("minlength: 9; maxlength: 8;")).is_err());
+        assert!(PasswordRules::decode(Some("max-consecutive: 0;")).is_err());
+    }
+
+    #[test]
+    fn test_rules_round_trip() {
+        let rules = PasswordRules::decode(Some(
+            "minlength: 10; required: lower; allowed: []-]; max-consecutive: 3;",
+        ))
+        .unwrap();
+
+        assert_eq!(PasswordRules::decode(Some(&rules.encode())).unwrap(), rules);
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    env,
+    fs::{self, DirBuilder, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::error::{ErrorCode::UnableToWriteOutput, GenError};
+
+/// Environment variable that moves the store, `.mokuba` in the home directory by default.
+pub const HOME_VAR: &str = "MOKUBA_HOME";
+
+// How long `lock` waits for another command to release a lock.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Directory where mokuba keeps what it stores, like the password entries and the certificates.
+pub fn home() -> PathBuf {
+    match env::var_os(HOME_VAR).filter(|home| !home.is_empty()) {
+        Some(home) => PathBuf::from(home),
+        None => env::var_os("HOME")
+            .or_else(|| env::var_os("USERPROFILE"))
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join(".mokuba"),
+    }
+}
+
+/**
+Creates `dir` and its missing parents, only accessible by their owner (0700), and returns it.
+
+This function will return an error if the directory cannot be created.
+*/
+pub fn create_dir(dir: &Path) -> Result<&Path, GenError> {
+    let mut builder = DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+
+    builder.create(dir).map(|_| dir).map_err(|e| {
+        GenError::new(
+            UnableToWriteOutput,
+            format!("Unable to create {} with error: {}", dir.display(), e),
+        )
+    })
+}
+
+/**
+Writes `content` to a new file at `path`, only readable and writable by its owner (0600).
+
+This function will return an error if the file already exists or cannot be written, existing files are never
+overwritten.
+*/
+pub fn write_private(path: &Path, content: &[u8]) -> Result<(), GenError> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(content).and_then(|_| file.sync_all()))
+        .map_err(|e| {
+            GenError::new(
+                UnableToWriteOutput,
+                format!("Unable to write {} with error: {}", path.display(), e),
+            )
+        })
+}
+
+/**
+Replaces the content of the file at `path`, or creates it, only readable and writable by its owner. The
+content is written to a temporary file next to it first and renamed over it, so the file is either the old or
+the new one, never half written.
+
+This function will return an error if the file cannot be written or renamed.
+*/
+pub fn replace_private(path: &Path, content: &[u8]) -> Result<(), GenError> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(format!(".{}.tmp", std::process::id()));
+    let temporary = PathBuf::from(temporary);
+
+    write_private(&temporary, content)?;
+    fs::rename(&temporary, path).map_err(|e| {
+        let _ = fs::remove_file(&temporary);
+        GenError::new(
+            UnableToWriteOutput,
+            format!("Unable to replace {} with error: {}", path.display(), e),
+        )
+    })
+}
+
+/// An exclusive lock on a file of the store, released when it is dropped.
+#[derive(Debug)]
+pub struct Lock {
+    path: PathBuf,
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/**
+Takes an exclusive lock on the file at `path`, held until the returned `Lock` is dropped, so commands that
+read a file and replace it never lose each other's changes. The lock is the file `<path>.lock`, created with
+`create_new`. While another command holds it, this function waits up to 10 seconds.
+
+This function will return an error if the lock is still held after that, or if it cannot be created.
+*/
+pub fn lock(path: &Path) -> Result<Lock, GenError> {
+    let mut lock = path.as_os_str().to_owned();
+    lock.push(".lock");
+    let lock = PathBuf::from(lock);
+    let start = Instant::now();
+
+    loop {
+        match write_private(&lock, std::process::id().to_string().as_bytes()) {
+            Ok(()) => return Ok(Lock { path: lock }),
+            Err(_) if lock.exists() && start.elapsed() < LOCK_TIMEOUT => {
+                thread::sleep(Duration::from_millis(50))
+            }
+            Err(_) if lock.exists() => {
+                return Err(GenError::new(
+                    UnableToWriteOutput,
+                    format!(
+                        "{} is used by another command, remove {} if none is running",
+                        path.display(),
+                        lock.display()
+                    ),
+                ))
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_private_overwrites_but_write_private_does_not() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store");
+
+        write_private(&path, b"old").unwrap();
+        assert_eq!(
+            write_private(&path, b"new").unwrap_err().code,
+            UnableToWriteOutput
+        );
+        replace_private(&path, b"new").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_lock_is_exclusive_until_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path, lock_file) = (dir.path().join("vault"), dir.path().join("vault.lock"));
+
+        let held = lock(&path).unwrap();
+        assert!(lock_file.exists());
+        let waiting = thread::spawn({
+            let path = path.clone();
+            move || lock(&path).map(|_| Instant::now())
+        });
+        thread::sleep(Duration::from_millis(200));
+        let released = Instant::now();
+        drop(held);
+
+        assert!(waiting.join().unwrap().unwrap() >= released);
+        assert!(!lock_file.exists());
+    }
+}
@@ -0,0 +1,139 @@
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    generator::derive::stretch,
+    mstd::{
+        error::{
+            ErrorCode::{UnableToDecrypt, UnableToEncodeT},
+            GenError,
+        },
+        param::Kdf,
+    },
+};
+
+/// Length in bytes of the keys and the salt.
+pub const KEY_LENGTH: usize = 32;
+pub const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+
+pub type Key = [u8; KEY_LENGTH];
+
+/// Returns a key drawn from `rng`.
+pub fn random_key<R: RngCore + CryptoRng>(rng: &mut R) -> Key {
+    let mut key = [0u8; KEY_LENGTH];
+    rng.fill_bytes(&mut key);
+    key
+}
+
+/**
+Derives the key that seals the vault key from the master password, with Argon2id. With a keyfile, the SHA-256
+hash of its content is stretched along with the master password, so both are needed.
+
+# Examples
+
+```
+use mcore::{mstd::param::Kdf, vault::crypto::master_key};
+
+let kdf = Kdf::new(64, 1, 1);
+let key = master_key("correct horse", Some(b"keyfile"), b"saltsaltsaltsalt", &kdf).unwrap();
+
+assert_ne!(key, master_key("correct horse", None, b"saltsaltsaltsalt", &kdf).unwrap());
+assert_ne!(key, master_key("correct horse", Some(b"other"), b"saltsaltsaltsalt", &kdf).unwrap());
+```
+
+This function will return an error if the cost in `kdf` is rejected by Argon2.
+*/
+pub fn master_key(
+    master: &str,
+    keyfile: Option<&[u8]>,
+    salt: &[u8],
+    kdf: &Kdf,
+) -> Result<Key, GenError> {
+    // The hash has a fixed length, so it cannot be confused with a part of the master password.
+    let mut secret = keyfile
+        .map(|content| Sha256::digest(content).to_vec())
+        .unwrap_or_default();
+    secret.extend_from_slice(master.as_bytes());
+
+    let mut key = [0u8; KEY_LENGTH];
+    key.copy_from_slice(&stretch(&secret, salt, kdf, KEY_LENGTH)?);
+    Ok(key)
+}
+
+/**
+
+## Authenticated Encryption
+
+### Examples
+```
+use mcore::vault::crypto::{open, random_key, seal};
+use rand::rngs::OsRng;
+
+let key = random_key(&mut OsRng);
+let sealed = seal(&key, b"entry", b"hunter2", &mut OsRng).unwrap();
+
+assert_eq!(open(&key, b"entry", &sealed).unwrap(), b"hunter2");
+assert!(open(&key, b"key", &sealed).is_err());
+assert!(open(&random_key(&mut OsRng), b"entry", &sealed).is_err());
+```
+
+Encrypts `plaintext` with XChaCha20-Poly1305 under `key` and a random nonce, which is returned in front of
+the ciphertext. `context` is authenticated along with it, so a sealed value only opens where it belongs.
+
+This function will return an error if the plaintext is too long to be encrypted.
+*/
+pub fn seal<R: RngCore + CryptoRng>(
+    key: &Key,
+    context: &[u8],
+    plaintext: &[u8],
+    rng: &mut R,
+) -> Result<Vec<u8>, GenError> {
+    let mut nonce = [0u8; NONCE_LENGTH];
+    rng.fill_bytes(&mut nonce);
+
+    let ciphertext = XChaCha20Poly1305::new(key.into())
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: context,
+            },
+        )
+        .map_err(|_| GenError::new(UnableToEncodeT, "Unable to encrypt".to_owned()))?;
+
+    Ok([nonce.to_vec(), ciphertext].concat())
+}
+
+/**
+Decrypts a value sealed by `seal` with the same `key` and `context`.
+
+This function will return an error if the key or the context are not the ones it was sealed with, or if it
+was tampered with.
+*/
+pub fn open(key: &Key, context: &[u8], sealed: &[u8]) -> Result<Vec<u8>, GenError> {
+    let error = || {
+        GenError::new(
+            UnableToDecrypt,
+            "Unable to decrypt, wrong master password or corrupted vault".to_owned(),
+        )
+    };
+    if sealed.len() < NONCE_LENGTH {
+        return Err(error());
+    }
+
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: context,
+            },
+        )
+        .map_err(|_| error())
+}
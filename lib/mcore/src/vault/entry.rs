@@ -0,0 +1,341 @@
+use mmacro::ConstructorM;
+use rand::{CryptoRng, RngCore};
+
+use crate::{
+    generator::token::uuid,
+    mstd::{
+        codec::Codec,
+        error::{
+            DecodeError,
+            ErrorCode::{EntryAlreadyExists, EntryNotFound, UnableToDecodeT},
+            GenError,
+        },
+    },
+};
+
+/// A previous password of an entry and when it was replaced, in seconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq, Eq, ConstructorM)]
+pub struct Revision {
+    pub password: String,
+    pub replaced: u64,
+}
+
+/// A password kept in the vault, along with what it is for. `id` is a random UUID that never changes, even
+/// when the title does. `created` and `modified` are seconds since the Unix epoch. `history` keeps the
+/// previous passwords, the most recent first.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Entry {
+    pub id: String,
+    pub title: String,
+    pub username: String,
+    pub password: String,
+    pub urls: Vec<String>,
+    pub notes: String,
+    pub tags: Vec<String>,
+    pub created: u64,
+    pub modified: u64,
+    pub history: Vec<Revision>,
+}
+
+impl Entry {
+    /// Creates an entry with a new id, created and modified at `now`.
+    pub fn new<R: RngCore + CryptoRng>(
+        title: String,
+        password: String,
+        now: u64,
+        rng: &mut R,
+    ) -> Entry {
+        Entry {
+            id: uuid(rng),
+            title,
+            password,
+            created: now,
+            modified: now,
+            ..Default::default()
+        }
+    }
+
+    /// Replaces the password at `now`, keeping the previous one first in the history, which is cut to the
+    /// `depth` most recent passwords. Setting the current password again changes nothing.
+    pub fn set_password(&mut self, password: String, now: u64, depth: usize) {
+        if password == self.password {
+            return;
+        }
+
+        let previous = std::mem::replace(&mut self.password, password);
+        self.history.insert(0, Revision::new(previous, now));
+        self.history.truncate(depth);
+        self.modified = now;
+    }
+
+    /**
+    Puts back the password of `version`, 1 being the previous one, at `now`. It leaves the history and the
+    replaced password is kept in it like with `set_password`, so a restore can be undone.
+
+    This function will return an error if the history has no such version, or if it is the current password,
+    which leaves the history as it is.
+    */
+    pub fn restore(&mut self, version: usize, now: u64, depth: usize) -> Result<(), GenError> {
+        let revision = match version.checked_sub(1).filter(|i| *i < self.history.len()) {
+            Some(i) if self.history[i].password == self.password => {
+                return Err(GenError::new(
+                    EntryAlreadyExists,
+                    format!(
+                        "Version {} of {} is already its current password",
+                        version, self.title
+                    ),
+                ))
+            }
+            Some(i) => self.history.remove(i),
+            None => {
+                return Err(GenError::new(
+                    EntryNotFound,
+                    format!(
+                        "{} has {} previous passwords, there is no version {}",
+                        self.title,
+                        self.history.len(),
+                        version
+                    ),
+                ))
+            }
+        };
+
+        self.set_password(revision.password, now, depth);
+        Ok(())
+    }
+}
+
+impl Codec<Entry> for Entry {
+    fn encode(&self) -> String {
+        let created = self.created.to_string();
+        let modified = self.modified.to_string();
+        let history = self
+            .history
+            .iter()
+            .map(|revision| format!("{}\t{}", revision.replaced, escape(&revision.password)))
+            .collect::<Vec<String>>();
+        let mut fields = vec![
+            ("id", &self.id),
+            ("title", &self.title),
+            ("username", &self.username),
+            ("password", &self.password),
+        ];
+        fields.extend(self.urls.iter().map(|url| ("url", url)));
+        fields.extend(self.tags.iter().map(|tag| ("tag", tag)));
+        fields.extend([
+            ("notes", &self.notes),
+            ("created", &created),
+            ("modified", &modified),
+        ]);
+
+        let mut encoded = fields
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(key, value)| format!("{}\t{}\n", key, escape(value)))
+            .collect::<String>();
+        // The time a password was replaced comes before it, so it is not escaped.
+        encoded.extend(
+            history
+                .iter()
+                .map(|revision| format!("history\t{}\n", revision)),
+        );
+        encoded
+    }
+
+    /**
+    Decodes one `<field>\t<value>` line per field, where tabs, line breaks and backslashes of the value are
+    escaped. `url` and `tag` lines are repeated once per value, and `history` lines once per previous
+    password as `history\t<replaced>\t<password>`, the most recent first. Missing fields are empty and unknown
+    ones are skipped. The id must be a UUID and the title cannot be empty.
+
+    # Examples
+
+    ```
+    use mcore::{mstd::codec::Codec, vault::entry::Entry};
+    use rand::rngs::OsRng;
+
+    let mut entry = Entry::new("mail".to_string(), "a\tb\\nc".to_string(), 1700000000, &mut OsRng);
+    entry.urls = vec!["https://mail.example.com".to_string()];
+    entry.tags = vec!["work".to_string(), "email".to_string()];
+    entry.notes = "Recovery email\nis the personal one".to_string();
+    entry.set_password("hunter2".to_string(), 1700000100, 10);
+
+    assert_eq!(Entry::decode(Some(&entry.encode())).unwrap(), entry);
+    assert!(Entry::decode(Some("title\tmail\npassword\thunter2\n")).is_err());
+    ```
+    */
+    fn decode(s: Option<&str>) -> Result<Entry, DecodeError> {
+        let mut entry = Entry::default();
+
+        for line in s.unwrap_or_default().lines() {
+            let (key, value) = line.split_once('\t').unwrap_or((line, ""));
+            if key == "history" {
+                let (replaced, password) = value.split_once('\t').unwrap_or((value, ""));
+                entry.history.push(Revision::new(
+                    unescape(password)?,
+                    u64::decode(Some(replaced))?,
+                ));
+                continue;
+            }
+
+            let value = unescape(value)?;
+            match key {
+                "id" => entry.id = value,
+                "title" => entry.title = value,
+                "username" => entry.username = value,
+                "password" => entry.password = value,
+                "url" => entry.urls.push(value),
+                "tag" => entry.tags.push(value),
+                "notes" => entry.notes = value,
+                "created" => entry.created = u64::decode(Some(&value))?,
+                "modified" => entry.modified = u64::decode(Some(&value))?,
+                _ => (),
+            }
+        }
+
+        if !is_uuid(&entry.id) {
+            return Err(DecodeError::new(
+                UnableToDecodeT,
+                format!(
+                    "Unable to decode the entry {}, {:?} is not a UUID",
+                    entry.title, entry.id
+                ),
+            ));
+        }
+        match entry.title.is_empty() {
+            true => Err(DecodeError::new(
+                UnableToDecodeT,
+                "Unable to decode an entry without a title".to_owned(),
+            )),
+            false => Ok(entry),
+        }
+    }
+
+    fn is(s: String) -> bool {
+        Entry::decode(Some(&s)).is_ok()
+    }
+}
+
+// Whether `s` is a hyphenated UUID, in any case.
+fn is_uuid(s: &str) -> bool {
+    let groups = s
+        .split('-')
+        .map(|group| group.len())
+        .collect::<Vec<usize>>();
+
+    groups == [8, 4, 4, 4, 12] && s.chars().all(|c| c == '-' || c.is_ascii_hexdigit())
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape(s: &str) -> Result<String, DecodeError> {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.push(match chars.next() {
+                Some('\\') => '\\',
+                Some('t') => '\t',
+                Some('n') => '\n',
+                Some('r') => '\r',
+                _ => {
+                    return Err(DecodeError::new(
+                        UnableToDecodeT,
+                        format!("Invalid escape sequence in {}", s),
+                    ))
+                }
+            }),
+            c => unescaped.push(c),
+        }
+    }
+
+    Ok(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_entry_keeps_its_id_and_skips_empty_fields() {
+        let entry = Entry::new("mail".to_string(), "hunter2".to_string(), 42, &mut OsRng);
+        let encoded = entry.encode();
+
+        assert!(is_uuid(&entry.id));
+        assert_ne!(
+            entry.id,
+            Entry::new("mail".to_string(), String::new(), 42, &mut OsRng).id
+        );
+        assert_eq!(
+            encoded,
+            format!(
+                "id\t{}\ntitle\tmail\npassword\thunter2\ncreated\t42\nmodified\t42\n",
+                entry.id
+            )
+        );
+        assert_eq!(
+            Entry::decode(Some(&format!("{}color\tblue\n", encoded))).unwrap(),
+            entry
+        );
+    }
+
+    #[test]
+    fn test_entry_keeps_the_most_recent_passwords() {
+        let mut entry = Entry::new("mail".to_string(), "v1".to_string(), 0, &mut OsRng);
+        for (i, password) in ["v2", "v3", "v3", "v4"].into_iter().enumerate() {
+            entry.set_password(password.to_string(), i as u64 + 1, 2);
+        }
+
+        assert_eq!(entry.password, "v4");
+        assert_eq!(entry.modified, 4);
+        assert_eq!(
+            entry.history,
+            [
+                Revision::new("v3".to_string(), 4),
+                Revision::new("v2".to_string(), 2)
+            ]
+        );
+
+        entry.restore(2, 5, 2).unwrap();
+        assert_eq!(entry.password, "v2");
+        assert_eq!(entry.history[0], Revision::new("v4".to_string(), 5));
+        assert_eq!(entry.restore(3, 6, 2).unwrap_err().code, EntryNotFound);
+        assert!(entry.restore(0, 6, 2).is_err());
+        assert_eq!(Entry::decode(Some(&entry.encode())).unwrap(), entry);
+    }
+
+    #[test]
+    fn test_entry_restore_keeps_a_revision_equal_to_the_current_password() {
+        let mut entry = Entry::new("mail".to_string(), "v1".to_string(), 0, &mut OsRng);
+        entry.set_password("v2".to_string(), 1, 10);
+        entry.set_password("v1".to_string(), 2, 10);
+        let history = entry.history.clone();
+
+        assert_eq!(
+            entry.restore(2, 3, 10).unwrap_err().code,
+            EntryAlreadyExists
+        );
+        assert_eq!((entry.password.as_str(), entry.modified), ("v1", 2));
+        assert_eq!(entry.history, history);
+        entry.restore(1, 3, 10).unwrap();
+        assert_eq!(entry.password, "v2");
+    }
+
+    #[test]
+    fn test_entry_is_err_when_invalid() {
+        let id = "id\t0f8fad5b-d9cb-469f-a165-70867728950e\n";
+
+        assert!(Entry::decode(Some(&format!("{}title\tmail\n", id))).is_ok());
+        assert!(Entry::decode(Some(id)).is_err());
+        assert!(Entry::decode(Some(&format!("{}title\tmail\\x\n", id))).is_err());
+        assert!(Entry::decode(Some(&format!("{}title\tmail\ncreated\tnow\n", id))).is_err());
+        assert!(Entry::decode(Some("id\tnot-a-uuid\ntitle\tmail\n")).is_err());
+    }
+}
@@ -0,0 +1,712 @@
+// Encrypted vault of password entries, kept in the store.
+pub mod crypto;
+pub mod entry;
+
+use rand::{CryptoRng, RngCore};
+use std::{fs, path::Path};
+
+use self::{
+    crypto::{master_key, open, random_key, seal, Key, SALT_LENGTH},
+    entry::Entry,
+};
+use crate::mstd::{
+    codec::Codec,
+    encoding::{base64_decode, base64_encode},
+    error::{
+        DecodeError,
+        ErrorCode::{
+            EntryAlreadyExists, EntryNotFound, UnableToDecodeT, UnableToDecrypt, UnableToDeriveKey,
+            UnableToReadFile,
+        },
+        GenError,
+    },
+    param::Kdf,
+    store::{replace_private, write_private},
+};
+
+// First line of every vault file, bumped when the format changes.
+const FORMAT: &str = "mokuba-vault 1";
+// Contexts the vault key and the entries are sealed with, the entries along with the header of the vault.
+const KEY_CONTEXT: &[u8] = b"mokuba-vault key";
+const ENTRIES_CONTEXT: &str = "mokuba-vault entries";
+
+/// Amount of previous passwords kept per entry, unless the vault is created with another one.
+pub const DEFAULT_HISTORY: u32 = 10;
+/// Length in bytes of the keyfiles written by `generate_keyfile`.
+pub const KEYFILE_LENGTH: usize = 64;
+
+/// The vault as it is stored: the cost and salt of the master key, whether a keyfile is needed along with the
+/// master password, how many previous passwords are kept per entry, the vault key sealed with the master key
+/// and the entries sealed with the vault key. The entries are sealed at once, along with the header of the
+/// vault, so no entry can be dropped, duplicated or replaced by an older one, and the header cannot be
+/// changed, without the vault failing to unlock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SealedVault {
+    pub kdf: Kdf,
+    pub salt: Vec<u8>,
+    pub keyfile: bool,
+    pub history: u32,
+    pub key: Vec<u8>,
+    pub entries: Vec<u8>,
+}
+
+impl Codec<SealedVault> for SealedVault {
+    fn encode(&self) -> String {
+        let mut lines = vec![FORMAT.to_string()];
+        lines.extend(self.header());
+        lines.push(format!("entries {}", base64_encode(&self.entries)));
+
+        lines.join("\n") + "\n"
+    }
+
+    /**
+    Decodes a vault file: the format line, then the `argon2id m=<KiB> t=<iterations> p=<parallelism> <salt>`
+    line, an optional `keyfile sha256` line when a keyfile is needed, an optional `history <depth>` line, the
+    `key <sealed key>` line and the `entries <sealed entries>` line, in base64.
+
+    # Examples
+
+    ```
+    use mcore::{mstd::{codec::Codec, param::Kdf}, vault::SealedVault};
+
+    let vault = SealedVault::decode(Some(
+        "mokuba-vault 1\nargon2id m=64 t=1 p=1 AAAA\nkey AQID\nentries BAU=\n",
+    )).unwrap();
+
+    assert_eq!(vault.kdf, Kdf::new(64, 1, 1));
+    assert_eq!(vault.key, [1, 2, 3]);
+    assert_eq!(vault.history, 10);
+    assert!(!vault.keyfile);
+    assert_eq!(vault.entries, [4, 5]);
+    assert!(SealedVault::decode(Some("mokuba-vault 1\nargon2id m=64 t=1 p=1 AAAA\nkey AQID\n")).is_err());
+    assert!(SealedVault::decode(Some("argon2id m=64 t=1 p=1 AAAA\nkey AQID\nentries BAU=\n")).is_err());
+    ```
+    */
+    fn decode(s: Option<&str>) -> Result<SealedVault, DecodeError> {
+        let mut lines = s.unwrap_or_default().lines().map(|line| line.trim());
+
+        if lines.next() != Some(FORMAT) {
+            return Err(error("This is not a mokuba vault".to_owned()));
+        }
+
+        let kdf = lines.next().unwrap_or_default();
+        let (kdf, salt) = match kdf.split_whitespace().collect::<Vec<&str>>()[..] {
+            ["argon2id", memory, iterations, parallelism, salt] => {
+                let cost = |field: &str, key: &str| match field.split_once('=') {
+                    Some((name, value)) if name == key => u32::decode(Some(value)),
+                    _ => Err(error(format!(
+                        "Unable to decode the {} cost of {}",
+                        key, kdf
+                    ))),
+                };
+                (
+                    Kdf::new(
+                        cost(memory, "m")?,
+                        cost(iterations, "t")?,
+                        cost(parallelism, "p")?,
+                    ),
+                    base64_decode(salt)?,
+                )
+            }
+            _ => return Err(error(format!("Unsupported key derivation {}", kdf))),
+        };
+
+        let sealed = |name: &str, line: &str| match line.split_once(' ') {
+            Some((key, value)) if key == name => base64_decode(value),
+            _ => Err(error(format!("Expected a {} line, not {}", name, line))),
+        };
+        let (mut keyfile, mut history) = (false, DEFAULT_HISTORY);
+        let mut line = lines.next().unwrap_or_default();
+        loop {
+            match line.split_once(' ') {
+                Some(("keyfile", "sha256")) => keyfile = true,
+                Some(("keyfile", hash)) => {
+                    return Err(error(format!("Unsupported keyfile hash {}", hash)))
+                }
+                Some(("history", depth)) => history = u32::decode(Some(depth))?,
+                _ => break,
+            }
+            line = lines.next().unwrap_or_default();
+        }
+        let key = sealed("key", line)?;
+        let mut lines = lines.filter(|line| !line.is_empty());
+        let entries = sealed("entries", lines.next().unwrap_or_default())?;
+        if let Some(line) = lines.next() {
+            return Err(error(format!(
+                "Unexpected line after the entries: {}",
+                line
+            )));
+        }
+
+        Ok(SealedVault {
+            kdf,
+            salt,
+            keyfile,
+            history,
+            key,
+            entries,
+        })
+    }
+
+    fn is(s: String) -> bool {
+        SealedVault::decode(Some(&s)).is_ok()
+    }
+}
+
+impl SealedVault {
+    // The lines between the format and the entries, in the order they are written.
+    fn header(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "argon2id m={} t={} p={} {}",
+            self.kdf.memory,
+            self.kdf.iterations,
+            self.kdf.parallelism,
+            base64_encode(&self.salt)
+        )];
+        if self.keyfile {
+            lines.push("keyfile sha256".to_string());
+        }
+        lines.push(format!("history {}", self.history));
+        lines.push(format!("key {}", base64_encode(&self.key)));
+        lines
+    }
+
+    // Context the entries are sealed with, which binds them to the header.
+    fn context(&self) -> Vec<u8> {
+        [ENTRIES_CONTEXT.to_string(), self.header().join("\n")]
+            .join("\n")
+            .into_bytes()
+    }
+
+    /**
+    Derives the master key from `master` and the content of the `keyfile`, if the vault needs one, and opens
+    the vault key and the entries with it.
+
+    This function will return an error if the master password or the keyfile are wrong, if a keyfile is
+    missing or not needed, or if the vault was tampered with.
+    */
+    pub fn unlock(&self, master: &str, keyfile: Option<&[u8]>) -> Result<Vault, GenError> {
+        match (self.keyfile, keyfile.is_some()) {
+            (true, false) => {
+                return Err(GenError::new(
+                    UnableToDecrypt,
+                    "This vault needs a keyfile along with the master password".to_owned(),
+                ))
+            }
+            (false, true) => {
+                return Err(GenError::new(
+                    UnableToDecrypt,
+                    "This vault does not use a keyfile".to_owned(),
+                ))
+            }
+            _ => (),
+        }
+
+        let master_key = master_key(master, keyfile, &self.salt, &self.kdf)?;
+        let key = open(&master_key, KEY_CONTEXT, &self.key)?
+            .try_into()
+            .map_err(|_| GenError::new(UnableToDecrypt, "The vault key is invalid".to_owned()))?;
+
+        let plaintext = String::from_utf8(open(&key, &self.context(), &self.entries)?)
+            .map_err(|e| GenError::new(UnableToDecodeT, e.to_string()))?;
+        // Entries never contain an empty line, so one is left between them.
+        let entries = plaintext
+            .split("\n\n")
+            .filter(|encoded| !encoded.is_empty())
+            .map(|encoded| Entry::decode(Some(encoded)).map_err(|e| GenError::new(e.code, e.cause)))
+            .collect::<Result<Vec<Entry>, GenError>>()?;
+
+        Ok(Vault {
+            kdf: self.kdf,
+            salt: self.salt.clone(),
+            keyfile: self.keyfile,
+            history: self.history,
+            sealed_key: self.key.clone(),
+            key,
+            entries,
+        })
+    }
+}
+
+/// An unlocked vault, whose entries can be read and changed before it is sealed again.
+#[derive(Debug, Clone)]
+pub struct Vault {
+    kdf: Kdf,
+    salt: Vec<u8>,
+    keyfile: bool,
+    pub history: u32,
+    sealed_key: Vec<u8>,
+    key: Key,
+    pub entries: Vec<Entry>,
+}
+
+impl Vault {
+    /**
+
+    ## Vault
+
+    ### Examples
+    ```
+    use mcore::{mstd::param::Kdf, vault::{entry::Entry, Vault}};
+    use rand::rngs::OsRng;
+
+    let mut vault = Vault::create("correct horse", None, Kdf::new(64, 1, 1), &mut OsRng).unwrap();
+    vault.add(Entry::new("mail".to_string(), "hunter2".to_string(), 0, &mut OsRng)).unwrap();
+
+    let sealed = vault.seal(&mut OsRng).unwrap();
+    assert_eq!(sealed.unlock("correct horse", None).unwrap().get("mail").unwrap().password, "hunter2");
+    assert!(sealed.unlock("wrong horse", None).is_err());
+    ```
+
+    Creates an empty vault with a random vault key, sealed with the key derived from `master` and the content
+    of the `keyfile`, if any, with `kdf`.
+
+    This function will return an error if the cost in `kdf` is rejected by Argon2.
+    */
+    pub fn create<R: RngCore + CryptoRng>(
+        master: &str,
+        keyfile: Option<&[u8]>,
+        kdf: Kdf,
+        rng: &mut R,
+    ) -> Result<Vault, GenError> {
+        let mut salt = vec![0u8; SALT_LENGTH];
+        rng.fill_bytes(&mut salt);
+        let key = random_key(rng);
+        let master_key = master_key(master, keyfile, &salt, &kdf)?;
+        let sealed_key = seal(&master_key, KEY_CONTEXT, &key, rng)?;
+
+        Ok(Vault {
+            kdf,
+            salt,
+            keyfile: keyfile.is_some(),
+            history: DEFAULT_HISTORY,
+            sealed_key,
+            key,
+            entries: vec![],
+        })
+    }
+
+    /// Cost of the derivation of the master key.
+    pub fn kdf(&self) -> Kdf {
+        self.kdf
+    }
+
+    /**
+    Seals the vault key with the key derived from the new `master` password and the content of the `keyfile`,
+    if any, with `kdf`, under a new salt. Every cost of `kdf` must be at least the current one, so the master
+    key never gets cheaper to brute force. With `rotate`, the vault key is replaced by a new one first, so
+    the entries are sealed with it on the next `seal` and the previous master password and vault key open
+    nothing that is saved afterwards.
+
+    This function will return an error if a cost in `kdf` is lower than the current one or is rejected by
+    Argon2.
+    */
+    pub fn rekey<R: RngCore + CryptoRng>(
+        &mut self,
+        master: &str,
+        keyfile: Option<&[u8]>,
+        kdf: Kdf,
+        rotate: bool,
+        rng: &mut R,
+    ) -> Result<(), GenError> {
+        let current = self.kdf;
+        if kdf.memory < current.memory
+            || kdf.iterations < current.iterations
+            || kdf.parallelism < current.parallelism
+        {
+            return Err(GenError::new(
+                UnableToDeriveKey,
+                format!(
+                    "The cost m={} t={} p={} is lower than the current one m={} t={} p={}, it can only be raised",
+                    kdf.memory,
+                    kdf.iterations,
+                    kdf.parallelism,
+                    current.memory,
+                    current.iterations,
+                    current.parallelism
+                ),
+            ));
+        }
+
+        let mut salt = vec![0u8; SALT_LENGTH];
+        rng.fill_bytes(&mut salt);
+        let key = match rotate {
+            true => random_key(rng),
+            false => self.key,
+        };
+        let master_key = master_key(master, keyfile, &salt, &kdf)?;
+        let sealed_key = seal(&master_key, KEY_CONTEXT, &key, rng)?;
+
+        self.kdf = kdf;
+        self.salt = salt;
+        self.keyfile = keyfile.is_some();
+        self.key = key;
+        self.sealed_key = sealed_key;
+        Ok(())
+    }
+
+    /// Seals every entry at once with the vault key, along with the header, under a new nonce.
+    pub fn seal<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<SealedVault, GenError> {
+        let mut sealed = SealedVault {
+            kdf: self.kdf,
+            salt: self.salt.clone(),
+            keyfile: self.keyfile,
+            history: self.history,
+            key: self.sealed_key.clone(),
+            entries: vec![],
+        };
+        let plaintext = self
+            .entries
+            .iter()
+            .map(|entry| entry.encode())
+            .collect::<Vec<String>>()
+            .join("\n");
+        sealed.entries = seal(&self.key, &sealed.context(), plaintext.as_bytes(), rng)?;
+
+        Ok(sealed)
+    }
+
+    /// Titles of the entries, sorted.
+    pub fn titles(&self) -> Vec<&str> {
+        let mut titles = self
+            .entries
+            .iter()
+            .map(|entry| entry.title.as_str())
+            .collect::<Vec<&str>>();
+        titles.sort_unstable_by_key(|title| title.to_lowercase());
+        titles
+    }
+
+    /// Returns the entry titled `title`, ignoring case, or whose id is `title`.
+    pub fn get(&self, title: &str) -> Result<&Entry, GenError> {
+        self.position(title).map(|i| &self.entries[i])
+    }
+
+    /// Adds `entry`, unless another one already has its title or its id.
+    pub fn add(&mut self, entry: Entry) -> Result<(), GenError> {
+        if self.position(&entry.title).is_ok() || self.position(&entry.id).is_ok() {
+            return Err(GenError::new(
+                EntryAlreadyExists,
+                format!("An entry titled {} already exists", entry.title),
+            ));
+        }
+
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Replaces the password of the entry titled `title`, or whose id is `title`, keeping the previous one.
+    pub fn set_password(
+        &mut self,
+        title: &str,
+        password: String,
+        now: u64,
+    ) -> Result<&Entry, GenError> {
+        let i = self.position(title)?;
+        self.entries[i].set_password(password, now, self.history as usize);
+        Ok(&self.entries[i])
+    }
+
+    /// Puts back the password of `version` of the entry titled `title`, or whose id is `title`, see
+    /// `Entry::restore`.
+    pub fn restore(&mut self, title: &str, version: usize, now: u64) -> Result<&Entry, GenError> {
+        let i = self.position(title)?;
+        self.entries[i].restore(version, now, self.history as usize)?;
+        Ok(&self.entries[i])
+    }
+
+    /// Removes and returns the entry titled `title`, ignoring case, or whose id is `title`.
+    pub fn remove(&mut self, title: &str) -> Result<Entry, GenError> {
+        self.position(title).map(|i| self.entries.remove(i))
+    }
+
+    fn position(&self, title: &str) -> Result<usize, GenError> {
+        self.entries
+            .iter()
+            .position(|entry| {
+                entry.title.to_lowercase() == title.to_lowercase() || entry.id == title
+            })
+            .ok_or_else(|| GenError::new(EntryNotFound, format!("No entry titled {}", title)))
+    }
+}
+
+/**
+Reads the vault stored at `path` and unlocks it with `master` and the content of the `keyfile`, if any.
+
+This function will return an error if the file cannot be read, is not a vault or if the master password or
+the keyfile are wrong.
+*/
+pub fn load(path: &Path, master: &str, keyfile: Option<&[u8]>) -> Result<Vault, GenError> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        GenError::new(
+            UnableToReadFile,
+            format!(
+                "Unable to read the vault {} with error: {}",
+                path.display(),
+                e
+            ),
+        )
+    })?;
+
+    SealedVault::decode(Some(&content))
+        .map_err(|e| GenError::new(e.code, e.cause))?
+        .unlock(master, keyfile)
+}
+
+/**
+Writes a new keyfile of `KEYFILE_LENGTH` bytes drawn from `rng` to `path`, only readable and writable by its
+owner (0600).
+
+This function will return an error if the file already exists or cannot be written.
+*/
+pub fn generate_keyfile<R: RngCore + CryptoRng>(path: &Path, rng: &mut R) -> Result<(), GenError> {
+    let mut content = [0u8; KEYFILE_LENGTH];
+    rng.fill_bytes(&mut content);
+    write_private(path, &content)
+}
+
+/**
+Reads the keyfile at `path`, which can be any file.
+
+This function will return an error if the file cannot be read or is empty.
+*/
+pub fn read_keyfile(path: &Path) -> Result<Vec<u8>, GenError> {
+    let error = |cause: String| {
+        GenError::new(
+            UnableToReadFile,
+            format!("Unable to read the keyfile {} {}", path.display(), cause),
+        )
+    };
+
+    match fs::read(path) {
+        Ok(content) if content.is_empty() => Err(error("because it is empty".to_owned())),
+        Ok(content) => Ok(content),
+        Err(e) => Err(error(format!("with error: {}", e))),
+    }
+}
+
+/**
+Seals `vault` and writes it to `path`, atomically. With `create`, the file must not exist yet.
+
+This function will return an error if the file cannot be written, or already exists with `create`.
+*/
+pub fn save<R: RngCore + CryptoRng>(
+    path: &Path,
+    vault: &Vault,
+    create: bool,
+    rng: &mut R,
+) -> Result<(), GenError> {
+    let content = vault.seal(rng)?.encode();
+
+    match create {
+        true => write_private(path, content.as_bytes()),
+        false => replace_private(path, content.as_bytes()),
+    }
+}
+
+fn error(cause: String) -> DecodeError {
+    DecodeError::new(UnableToDecodeT, cause)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    fn vault() -> Vault {
+        Vault::create("master", None, Kdf::new(64, 1, 1), &mut OsRng).unwrap()
+    }
+
+    fn entry(title: &str, password: &str) -> Entry {
+        Entry::new(title.to_string(), password.to_string(), 0, &mut OsRng)
+    }
+
+    #[test]
+    fn test_vault_round_trip() {
+        let mut vault = vault();
+        vault.add(entry("mail", "hunter2")).unwrap();
+        vault
+            .add(Entry {
+                username: "alice".to_string(),
+                urls: vec!["https://bank.example.com".to_string()],
+                tags: vec!["finance".to_string()],
+                notes: "tab\tand\nbreak".to_string(),
+                ..entry("Bank", "1234")
+            })
+            .unwrap();
+
+        let encoded = vault.seal(&mut OsRng).unwrap().encode();
+        assert!(!encoded.contains("hunter2"));
+
+        let unlocked = SealedVault::decode(Some(&encoded))
+            .unwrap()
+            .unlock("master", None)
+            .unwrap();
+        assert_eq!(unlocked.entries, vault.entries);
+        assert_eq!(unlocked.titles(), ["Bank", "mail"]);
+    }
+
+    #[test]
+    fn test_vault_titles_are_unique_ignoring_case() {
+        let mut vault = vault();
+        let mail = entry("mail", "a");
+        vault.add(mail.clone()).unwrap();
+
+        let duplicate = vault.add(entry("MAIL", "b"));
+        assert_eq!(duplicate.unwrap_err().code, EntryAlreadyExists);
+        let same_id = Entry {
+            title: "other".to_string(),
+            ..mail.clone()
+        };
+        assert_eq!(vault.add(same_id).unwrap_err().code, EntryAlreadyExists);
+        assert_eq!(vault.get(&mail.id).unwrap().title, "mail");
+        assert_eq!(vault.remove("Mail").unwrap().password, "a");
+        assert_eq!(vault.get("mail").unwrap_err().code, EntryNotFound);
+    }
+
+    #[test]
+    fn test_vault_keeps_history_up_to_its_depth() {
+        let mut vault = vault();
+        vault.history = 1;
+        vault.add(entry("mail", "v1")).unwrap();
+        vault.set_password("mail", "v2".to_string(), 1).unwrap();
+        vault.set_password("MAIL", "v3".to_string(), 2).unwrap();
+
+        let sealed = vault.seal(&mut OsRng).unwrap();
+        let mut unlocked = SealedVault::decode(Some(&sealed.encode()))
+            .unwrap()
+            .unlock("master", None)
+            .unwrap();
+        assert_eq!(unlocked.history, 1);
+        assert_eq!(unlocked.get("mail").unwrap().history.len(), 1);
+
+        let entry = unlocked.restore("mail", 1, 3).unwrap();
+        assert_eq!((entry.password.as_str(), entry.modified), ("v2", 3));
+        assert_eq!(entry.history[0].password, "v3");
+    }
+
+    #[test]
+    fn test_vault_rekey_changes_the_master_password_and_the_vault_key() {
+        let mut vault = vault();
+        vault.add(entry("mail", "hunter2")).unwrap();
+        let before = vault.seal(&mut OsRng).unwrap();
+
+        vault
+            .rekey("new master", None, Kdf::new(128, 2, 1), false, &mut OsRng)
+            .unwrap();
+        let kept = vault.seal(&mut OsRng).unwrap();
+        assert_eq!(kept.kdf, Kdf::new(128, 2, 1));
+        assert!(kept.unlock("master", None).is_err());
+        assert_eq!(
+            kept.unlock("new master", None).unwrap().entries,
+            before.unlock("master", None).unwrap().entries
+        );
+
+        for weaker in [Kdf::new(64, 2, 1), Kdf::new(128, 1, 1)] {
+            assert_eq!(
+                vault
+                    .rekey("new master", None, weaker, true, &mut OsRng)
+                    .unwrap_err()
+                    .code,
+                UnableToDeriveKey
+            );
+        }
+        assert_eq!(vault.kdf(), Kdf::new(128, 2, 1));
+
+        let old_key = before.unlock("master", None).unwrap().key;
+        vault
+            .rekey("new master", None, Kdf::new(128, 2, 1), true, &mut OsRng)
+            .unwrap();
+        let rotated = vault.seal(&mut OsRng).unwrap();
+        assert_eq!(
+            open(&old_key, &kept.context(), &kept.entries).unwrap(),
+            vault.entries[0].encode().as_bytes()
+        );
+        assert!(open(&old_key, &rotated.context(), &rotated.entries).is_err());
+        assert_eq!(
+            rotated.unlock("new master", None).unwrap().entries,
+            vault.entries
+        );
+    }
+
+    #[test]
+    fn test_vault_needs_its_keyfile() {
+        let mut vault =
+            Vault::create("master", Some(b"key"), Kdf::new(64, 1, 1), &mut OsRng).unwrap();
+        let encoded = vault.seal(&mut OsRng).unwrap().encode();
+        assert!(encoded.contains("\nkeyfile sha256\n"));
+
+        let sealed = SealedVault::decode(Some(&encoded)).unwrap();
+        assert!(sealed.keyfile);
+        assert!(sealed.unlock("master", Some(b"key")).is_ok());
+        assert!(sealed.unlock("master", Some(b"other")).is_err());
+        assert!(sealed.unlock("master", None).is_err());
+        assert!(sealed.unlock("other", Some(b"key")).is_err());
+
+        vault
+            .rekey("master", None, Kdf::new(64, 1, 1), false, &mut OsRng)
+            .unwrap();
+        let sealed = vault.seal(&mut OsRng).unwrap();
+        assert!(!sealed.encode().contains("keyfile"));
+        assert!(sealed.unlock("master", Some(b"key")).is_err());
+        assert!(sealed.unlock("master", None).is_ok());
+    }
+
+    #[test]
+    fn test_vault_is_err_when_tampered_with() {
+        let mut vault = vault();
+        vault.add(entry("mail", "hunter2")).unwrap();
+        let sealed = vault.seal(&mut OsRng).unwrap();
+        assert_eq!(
+            sealed.unlock("other", None).unwrap_err().code,
+            UnableToDecrypt
+        );
+
+        let mut flipped = sealed.clone();
+        flipped.entries[30] ^= 1;
+        let history = SealedVault {
+            history: 0,
+            ..sealed.clone()
+        };
+        // The same vault key sealed again still opens, but the entries are bound to the previous header.
+        let master_key = master_key("master", None, &vault.salt, &vault.kdf).unwrap();
+        vault.sealed_key = seal(&master_key, KEY_CONTEXT, &vault.key, &mut OsRng).unwrap();
+        let header = SealedVault {
+            key: vault.sealed_key.clone(),
+            ..sealed.clone()
+        };
+        for tampered in [flipped, history, header] {
+            assert_eq!(
+                tampered.unlock("master", None).unwrap_err().code,
+                UnableToDecrypt
+            );
+        }
+    }
+
+    #[test]
+    fn test_save_never_overwrites_on_create() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault");
+        let vault = vault();
+
+        save(&path, &vault, true, &mut OsRng).unwrap();
+        assert!(save(&path, &vault, true, &mut OsRng).is_err());
+        save(&path, &vault, false, &mut OsRng).unwrap();
+        assert!(load(&path, "master", None).unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn test_generate_keyfile_never_overwrites() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keyfile");
+
+        generate_keyfile(&path, &mut OsRng).unwrap();
+        let content = read_keyfile(&path).unwrap();
+        assert_eq!(content.len(), KEYFILE_LENGTH);
+        assert!(generate_keyfile(&path, &mut OsRng).is_err());
+        assert_eq!(read_keyfile(&path).unwrap(), content);
+
+        fs::write(&path, b"").unwrap();
+        assert_eq!(read_keyfile(&path).unwrap_err().code, UnableToReadFile);
+    }
+}
@@ -16,7 +16,7 @@ pub fn derive_constructor_mt(input: TokenStream) -> TokenStream {
     match fields {
         Ok(fields) => {
             let fields_types = fields.iter().clone().map(|f| f.ty.to_owned());
-            let is_named = fields.iter().clone().map(|f| f.ident.is_some()).all(|x| x);
+            let is_named = fields.iter().clone().all(|f| f.ident.is_some());
 
             if is_named {
                 let field_n = fields.iter().clone().map(|f| f.ident.to_owned());
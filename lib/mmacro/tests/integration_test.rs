@@ -15,6 +15,7 @@ fn test_derive_enum_variants() {
         UnableToDecodeT,
     }
 
+    #[allow(dead_code)]
     trait MokubaError<T>
     where
         T: Display + Debug,
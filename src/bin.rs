@@ -1,4 +1,4 @@
-use mcore::mstd::{deserialize, matcher, read_line_from, write_line_to};
+use mcore::mstd::{deserialize, matcher_to, read_line_from, write_line_to};
 use std::io::{stdin, stdout, Result as IOResult};
 
 fn main() -> IOResult<()> {
@@ -10,8 +10,8 @@ fn main() -> IOResult<()> {
         Ok(answer) => match deserialize(&answer) {
             Ok(command) => {
                 // TODO: Add help command to show all commands and their descriptions and help command per command
-                match matcher(&command) {
-                    Ok(input) => write_line_to(writer, &input),
+                match matcher_to(&command, writer.lock()) {
+                    Ok(()) => Ok(()),
                     Err(error) => write_line_to(writer, &error),
                 }
             }